@@ -0,0 +1,85 @@
+//! Measures the worst-case latency of a single wheel step when a large slot
+//! in a higher stack cascades
+//!
+//! Run with `cargo bench --bench cascade_latency`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+use timewarp::{entry::atomic, Wheel};
+
+/// The number of entries that share a single slot in the third stack
+const ENTRIES: u64 = 100_000;
+/// The first tick of that slot
+const SLOT_START: u64 = 1 << 16;
+/// The number of ticks covered by that slot
+const SLOT_LEN: u64 = 1 << 16;
+
+const BUDGETS: [usize; 4] = [0, 16, 64, 256];
+const ROUNDS: usize = 5;
+
+struct Report {
+    max_step: Duration,
+    mean_step: Duration,
+    max_cascade: usize,
+}
+
+fn run(budget: usize) -> Report {
+    let mut wheel = Wheel::<atomic::ArcEntry>::default();
+    wheel.set_cascade_budget(budget);
+
+    // the deadlines are spread evenly over the slot, in order, so none of
+    // them are held back by the entry in front of them
+    for index in 0..ENTRIES {
+        let delay = SLOT_START + index * SLOT_LEN / ENTRIES;
+        wheel.insert(atomic::Entry::new(delay));
+    }
+
+    let mut max_step = Duration::ZERO;
+    let mut total = Duration::ZERO;
+    let steps = SLOT_START + SLOT_LEN;
+
+    for _ in 0..steps {
+        let target = wheel.ticks() + 1;
+        let start = Instant::now();
+        wheel.advance_to(target);
+        black_box(wheel.wake(drop));
+        let elapsed = start.elapsed();
+
+        max_step = max_step.max(elapsed);
+        total += elapsed;
+    }
+
+    assert!(wheel.is_empty());
+
+    Report {
+        max_step,
+        mean_step: total / steps as u32,
+        max_cascade: wheel.stats().max_cascade,
+    }
+}
+
+fn main() {
+    println!(
+        "{} entries in one slot of the third stack, stepped one tick at a time",
+        ENTRIES
+    );
+    println!(
+        "{:>8} {:>14} {:>14} {:>12}",
+        "budget", "max step", "mean step", "max cascade"
+    );
+
+    for budget in BUDGETS.iter().copied() {
+        // the best of several rounds filters out preemption by the OS
+        let report = (0..ROUNDS)
+            .map(|_| run(budget))
+            .min_by_key(|report| report.max_step)
+            .unwrap();
+
+        println!(
+            "{:>8} {:>14?} {:>14?} {:>12}",
+            budget, report.max_step, report.mean_step, report.max_cascade
+        );
+    }
+}
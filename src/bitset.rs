@@ -1,26 +1,37 @@
-use core::mem::size_of;
-
 type Slot = usize;
-const SLOT_BITS: usize = size_of::<Slot>() * 8;
+const SLOT_BITS: usize = Slot::BITS as usize;
 const SLOT_BITS_U8: u8 = SLOT_BITS as u8;
 const SLOT_COUNT: usize = 256 / SLOT_BITS;
 
+/// One bit per slot, set if the slot has any bits set
+///
+/// This needs 16 bits on targets with a 16-bit `usize`.
+type Summary = u32;
+
+const _: () = assert!(SLOT_COUNT < Summary::BITS as usize);
+
 #[derive(Clone, Copy, Debug, Default)]
-pub struct Bitset([Slot; SLOT_COUNT]);
+pub struct Bitset {
+    slots: [Slot; SLOT_COUNT],
+    summary: Summary,
+}
 
 impl Bitset {
+    pub const EMPTY: Self = Self {
+        slots: [0; SLOT_COUNT],
+        summary: 0,
+    };
+
     pub fn is_empty(&self) -> bool {
-        self.0.iter().copied().all(|slot| slot == 0)
+        self.summary == 0
     }
 
-    pub fn len(&self) -> u8 {
-        self.0.iter().map(|slot| slot.count_ones() as u8).sum()
+    pub fn len(&self) -> u16 {
+        self.count(0, u8::MAX)
     }
 
-    #[allow(dead_code)]
     pub fn get(&self, index: u8) -> bool {
-        let slot = index / SLOT_BITS_U8;
-        let shift = index % SLOT_BITS_U8;
+        let (slot, shift) = split(index);
         let flag = 1 << shift;
         self.slot(slot) & flag != 0
     }
@@ -34,51 +45,209 @@ impl Bitset {
     }
 
     pub fn update(&mut self, index: u8, enabled: bool) {
-        let slot = index / SLOT_BITS_U8;
-        let shift = index % SLOT_BITS_U8;
+        let (slot_index, shift) = split(index);
         let flag = 1 << shift;
-        let slot = self.slot_mut(slot);
+        let slot = self.slot_mut(slot_index);
         if enabled {
             *slot |= flag;
         } else {
             *slot &= !flag;
         }
+
+        let occupied = *slot != 0;
+        let flag = 1 << slot_index;
+        if occupied {
+            self.summary |= flag;
+        } else {
+            self.summary &= !flag;
+        }
     }
 
-    pub fn next_occupied(&self, mut index: u8) -> Option<u8> {
-        let mut slot_index = index / SLOT_BITS_U8;
-        let mut shift = index % SLOT_BITS_U8;
+    /// Returns the first set index at or after `index`
+    pub fn next_occupied(&self, index: u8) -> Option<u8> {
+        let (slot_index, shift) = split(index);
 
-        loop {
-            let slot = self.slot(slot_index);
+        let slot = self.slot(slot_index) & mask_from(shift);
+        if slot != 0 {
+            return Some(join(slot_index, slot.trailing_zeros()));
+        }
 
-            let trailing = (slot >> shift).trailing_zeros() as u8;
+        // the remaining slots are found with the summary
+        let after = self.summary & (Summary::MAX << (slot_index + 1));
+        if after == 0 {
+            return None;
+        }
 
-            if trailing != SLOT_BITS_U8 {
-                return Some(index + trailing);
-            }
+        let slot_index = after.trailing_zeros() as u8;
+        Some(join(slot_index, self.slot(slot_index).trailing_zeros()))
+    }
+
+    /// Returns the first set index at or after `index`, continuing from 0 if
+    /// there are none
+    pub fn next_occupied_wrapping(&self, index: u8) -> Option<u8> {
+        self.next_occupied(index).or_else(|| self.first())
+    }
+
+    /// Returns the last set index at or before `index`
+    #[allow(dead_code)] // the wheel only searches forward
+    pub fn prev_occupied(&self, index: u8) -> Option<u8> {
+        let (slot_index, shift) = split(index);
+
+        let slot = self.slot(slot_index) & mask_to(shift);
+        if slot != 0 {
+            return Some(join(
+                slot_index,
+                SLOT_BITS as u32 - 1 - slot.leading_zeros(),
+            ));
+        }
+
+        let before = self.summary & ((1 << slot_index) - 1);
+        if before == 0 {
+            return None;
+        }
 
-            index = index.checked_add(SLOT_BITS_U8 - shift)?;
-            slot_index += 1;
-            shift = 0;
+        let slot_index = (Summary::BITS - 1 - before.leading_zeros()) as u8;
+        let slot = self.slot(slot_index);
+        Some(join(
+            slot_index,
+            SLOT_BITS as u32 - 1 - slot.leading_zeros(),
+        ))
+    }
+
+    pub fn first(&self) -> Option<u8> {
+        self.next_occupied(0)
+    }
+
+    #[allow(dead_code)] // the wheel only searches forward
+    pub fn last(&self) -> Option<u8> {
+        self.prev_occupied(u8::MAX)
+    }
+
+    /// Returns `true` if any index between `first` and `last` (inclusive) is set
+    pub fn any(&self, first: u8, last: u8) -> bool {
+        debug_assert!(first <= last);
+
+        let (first_slot, first_shift) = split(first);
+        let (last_slot, last_shift) = split(last);
+
+        if first_slot == last_slot {
+            let mask = mask_from(first_shift) & mask_to(last_shift);
+            return self.slot(first_slot) & mask != 0;
+        }
+
+        if self.slot(first_slot) & mask_from(first_shift) != 0 {
+            return true;
         }
+
+        if self.slot(last_slot) & mask_to(last_shift) != 0 {
+            return true;
+        }
+
+        self.summary_between(first_slot, last_slot) != 0
+    }
+
+    /// Returns the number of set indexes between `first` and `last` (inclusive)
+    pub fn count(&self, first: u8, last: u8) -> u16 {
+        debug_assert!(first <= last);
+
+        let (first_slot, first_shift) = split(first);
+        let (last_slot, last_shift) = split(last);
+
+        if first_slot == last_slot {
+            let mask = mask_from(first_shift) & mask_to(last_shift);
+            return (self.slot(first_slot) & mask).count_ones() as u16;
+        }
+
+        let mut count = (self.slot(first_slot) & mask_from(first_shift)).count_ones()
+            + (self.slot(last_slot) & mask_to(last_shift)).count_ones();
+
+        let mut between = self.summary_between(first_slot, last_slot);
+        while between != 0 {
+            let slot_index = between.trailing_zeros() as u8;
+            between &= between - 1;
+            count += self.slot(slot_index).count_ones();
+        }
+
+        count as u16
+    }
+
+    /// Iterates over the set indexes in ascending order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            slots: &self.slots,
+            summary: self.summary,
+            slot_index: 0,
+            slot: 0,
+        }
+    }
+
+    /// Returns the summary bits for the slots strictly between `first` and `last`
+    fn summary_between(&self, first: u8, last: u8) -> Summary {
+        self.summary & ((1 << last) - 1) & (Summary::MAX << (first + 1))
     }
 
     fn slot(&self, index: u8) -> &Slot {
         if cfg!(test) {
             debug_assert!(index < (SLOT_COUNT as u8));
         }
-        unsafe { self.0.get_unchecked(index as usize) }
+        unsafe { self.slots.get_unchecked(index as usize) }
     }
 
     fn slot_mut(&mut self, index: u8) -> &mut Slot {
         if cfg!(test) {
             debug_assert!(index < (SLOT_COUNT as u8));
         }
-        unsafe { self.0.get_unchecked_mut(index as usize) }
+        unsafe { self.slots.get_unchecked_mut(index as usize) }
     }
 }
 
+/// Iterator over the set indexes in a [`Bitset`]
+pub struct Iter<'a> {
+    slots: &'a [Slot; SLOT_COUNT],
+    /// The slots that haven't been loaded yet
+    summary: Summary,
+    slot_index: u8,
+    /// The bits of the current slot that haven't been yielded yet
+    slot: Slot,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.slot == 0 {
+            if self.summary == 0 {
+                return None;
+            }
+            self.slot_index = self.summary.trailing_zeros() as u8;
+            self.summary &= self.summary - 1;
+            self.slot = self.slots[self.slot_index as usize];
+        }
+
+        let shift = self.slot.trailing_zeros();
+        self.slot &= self.slot - 1;
+        Some(join(self.slot_index, shift))
+    }
+}
+
+fn split(index: u8) -> (u8, u8) {
+    (index / SLOT_BITS_U8, index % SLOT_BITS_U8)
+}
+
+fn join(slot_index: u8, shift: u32) -> u8 {
+    slot_index * SLOT_BITS_U8 + shift as u8
+}
+
+/// Selects the bits at or above `shift`
+fn mask_from(shift: u8) -> Slot {
+    Slot::MAX << shift
+}
+
+/// Selects the bits at or below `shift`
+fn mask_to(shift: u8) -> Slot {
+    Slot::MAX >> (SLOT_BITS_U8 - 1 - shift)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,4 +324,58 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn query_test() {
+        check!()
+            .with_type::<(Vec<Op>, u8, u8)>()
+            .for_each(move |(ops, a, b)| {
+                let mut bitset = Bitset::default();
+                let mut set = BTreeSet::new();
+
+                for op in ops.iter().copied() {
+                    match op {
+                        Op::Insert(i) => {
+                            bitset.insert(i);
+                            set.insert(i);
+                        }
+                        Op::Remove(i) => {
+                            bitset.remove(i);
+                            set.remove(&i);
+                        }
+                    }
+                }
+
+                // the summary has a bit for exactly the non-empty slots
+                for (index, slot) in bitset.slots.iter().enumerate() {
+                    assert_eq!(*slot != 0, bitset.summary & (1 << index) != 0);
+                }
+
+                assert_eq!(bitset.is_empty(), set.is_empty());
+                assert_eq!(bitset.len() as usize, set.len());
+                assert!(bitset.iter().eq(set.iter().copied()));
+                assert_eq!(bitset.first(), set.first().copied());
+                assert_eq!(bitset.last(), set.last().copied());
+
+                for index in 0..=255 {
+                    assert_eq!(
+                        bitset.prev_occupied(index),
+                        set.range(..=index).next_back().copied(),
+                        "index: {}",
+                        index
+                    );
+                    assert_eq!(
+                        bitset.next_occupied_wrapping(index),
+                        set.range(index..).next().or(set.first()).copied(),
+                        "index: {}",
+                        index
+                    );
+                }
+
+                let (first, last) = ((*a).min(*b), (*a).max(*b));
+                let count = set.range(first..=last).count();
+                assert_eq!(bitset.count(first, last) as usize, count);
+                assert_eq!(bitset.any(first, last), count > 0);
+            });
+    }
 }
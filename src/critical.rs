@@ -0,0 +1,175 @@
+//! A [`Wheel`] that can be shared with interrupt handlers
+//!
+//! [`SharedWheel`] guards the wheel with a [`critical_section::Mutex`] so
+//! timers can be inserted and cancelled from thread mode while a hardware
+//! timer interrupt drives the clock with [`SharedWheel::on_tick`]. Each
+//! operation holds the critical section only for as long as it touches the
+//! wheel; wake callbacks run outside of it.
+
+use crate::{
+    entry::{ConstQueue, ConstStorage, Entry, Storage, Tick},
+    Wheel,
+};
+use core::cell::RefCell;
+use critical_section::Mutex;
+
+/// A [`Wheel`] guarded by a critical section
+pub struct SharedWheel<E: Entry> {
+    wheel: Mutex<RefCell<Wheel<E>>>,
+}
+
+impl<E: Entry> SharedWheel<E>
+where
+    E::Queue: ConstQueue<E>,
+    E::Storage: ConstStorage<E>,
+{
+    /// Creates an empty wheel in a const context so it can be placed in a `static`
+    pub const fn new() -> Self {
+        Self {
+            wheel: Mutex::new(RefCell::new(Wheel::new())),
+        }
+    }
+}
+
+impl<E: Entry> Default for SharedWheel<E> {
+    fn default() -> Self {
+        Self::from_wheel(Wheel::default())
+    }
+}
+
+impl<E: Entry> SharedWheel<E> {
+    /// Wraps an existing wheel
+    pub fn from_wheel(wheel: Wheel<E>) -> Self {
+        Self {
+            wheel: Mutex::new(RefCell::new(wheel)),
+        }
+    }
+
+    /// Calls `f` with exclusive access to the wheel inside a critical section
+    pub fn with<R, F: FnOnce(&mut Wheel<E>) -> R>(&self, f: F) -> R {
+        critical_section::with(|cs| f(&mut self.wheel.borrow_ref_mut(cs)))
+    }
+
+    /// Inserts the entry to expire `delay` ticks from now
+    pub fn insert(&self, entry: E) {
+        self.with(|wheel| wheel.insert(entry))
+    }
+
+    /// Removes the entry from the wheel
+    ///
+    /// Returns `None` if the entry was not scheduled in this wheel
+    pub fn cancel(&self, entry: &E) -> Option<E> {
+        self.with(|wheel| wheel.cancel(entry))
+    }
+
+    /// Returns the current tick
+    pub fn ticks(&self) -> <E::Storage as Storage<E>>::Tick {
+        self.with(|wheel| wheel.ticks())
+    }
+
+    /// Returns the number of ticks until the earliest entry expires, capped
+    /// at `max_sleep`, which can be used to program a compare register before
+    /// entering a tickless idle
+    ///
+    /// See [`Wheel::next_alarm`].
+    pub fn next_alarm(
+        &self,
+        max_sleep: <E::Storage as Storage<E>>::Tick,
+    ) -> <E::Storage as Storage<E>>::Tick {
+        self.with(|wheel| wheel.next_alarm(max_sleep))
+    }
+
+    /// Advances the wheel by a single tick and wakes the expired entries
+    ///
+    /// Intended to be called from the timer interrupt. Each expired entry is
+    /// popped in its own critical section and `wake` is called with the
+    /// section released, so it may insert entries back into the wheel. Only
+    /// the entries that were pending when the tick started are woken; any
+    /// that `wake` inserts with a zero delay are left for the next tick.
+    ///
+    /// Returns the number of entries that were woken
+    pub fn on_tick<F: FnMut(E)>(&self, mut wake: F) -> usize {
+        let pending = self.with(|wheel| {
+            let target = wheel.ticks().wrapping_add(Tick::ONE);
+            wheel.advance_to(target);
+            wheel.pending_len()
+        });
+
+        let mut count = 0;
+        while count < pending {
+            let (entry, _info) = match self.with(|wheel| wheel.wake_one()) {
+                Some(woken) => woken,
+                // the callback cancelled one of the pending entries
+                None => break,
+            };
+            count += 1;
+            wake(entry);
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::fixed;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn on_tick_test() {
+        static WHEEL: SharedWheel<fixed::StaticEntry> = SharedWheel::new();
+        static ONE_SHOT: fixed::Entry = fixed::Entry::new(3);
+        static PERIODIC: fixed::Entry = fixed::Entry::new(2);
+        static CANCELLED: fixed::Entry = fixed::Entry::new(1);
+
+        WHEEL.insert(&ONE_SHOT);
+        WHEEL.insert(&PERIODIC);
+        WHEEL.insert(&CANCELLED);
+        assert!(WHEEL.cancel(&&CANCELLED).is_some());
+        assert_eq!(WHEEL.next_alarm(10), 2);
+
+        let mut woken = Vec::new();
+        for _ in 0..6 {
+            WHEEL.on_tick(|entry| {
+                woken.push((WHEEL.ticks(), entry.delay()));
+                // re-arm the periodic timer from within the callback
+                if core::ptr::eq(entry, &PERIODIC) {
+                    WHEEL.insert(entry);
+                }
+            });
+        }
+
+        assert_eq!(woken, [(2, 2), (3, 3), (4, 2), (6, 2)]);
+        assert_eq!(WHEEL.ticks(), 6);
+        assert!(PERIODIC.is_scheduled());
+    }
+
+    #[test]
+    fn zero_delay_test() {
+        static WHEEL: SharedWheel<fixed::StaticEntry> = SharedWheel::new();
+        static PERIODIC: fixed::Entry = fixed::Entry::new(0);
+        static ONE_SHOT: fixed::Entry = fixed::Entry::new(1);
+
+        WHEEL.insert(&PERIODIC);
+        WHEEL.insert(&ONE_SHOT);
+        assert_eq!(WHEEL.next_alarm(10), 0);
+
+        let mut woken = Vec::new();
+        for _ in 0..3 {
+            let count = WHEEL.on_tick(|entry| {
+                woken.push((WHEEL.ticks(), entry.delay()));
+                // re-arming with a zero delay must not keep the tick going
+                if core::ptr::eq(entry, &PERIODIC) {
+                    WHEEL.insert(entry);
+                }
+            });
+            assert!(count <= 2);
+        }
+
+        assert_eq!(woken, [(1, 0), (1, 1), (2, 0), (3, 0)]);
+        assert_eq!(WHEEL.next_alarm(10), 0);
+        assert!(WHEEL.cancel(&&PERIODIC).is_some());
+        assert_eq!(WHEEL.next_alarm(10), 10);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::stack::Stack;
-use core::ops;
+use core::{marker::PhantomData, ops};
 
 pub trait Entry: Sized {
     type Queue: Queue<Self>;
@@ -8,18 +8,138 @@ pub trait Entry: Sized {
     fn delay(&self) -> <Self::Storage as Storage<Self>>::Tick;
     fn start_tick(&self) -> <Self::Storage as Storage<Self>>::Tick;
     fn set_start_tick(&mut self, tick: <Self::Storage as Storage<Self>>::Tick);
+
+    /// Returns the absolute tick at which the entry expires
+    #[inline(always)]
+    fn deadline(&self) -> <Self::Storage as Storage<Self>>::Tick {
+        self.start_tick().wrapping_add(self.delay())
+    }
+
+    /// Returns `true` if the entry is currently held in a wheel
+    ///
+    /// Entries that can't tell report `false`, which disables the
+    /// [`Error::AlreadyScheduled`](crate::Error::AlreadyScheduled) check.
+    #[inline(always)]
+    fn is_scheduled(&self) -> bool {
+        false
+    }
+
+    /// Orders entries that expire on the same tick; lower values wake first
+    ///
+    /// Only used by queues that implement [`Queue::push_sorted`].
+    #[inline(always)]
+    fn priority(&self) -> u32 {
+        0
+    }
+
+    /// Called when the wheel schedules the entry
+    #[inline(always)]
+    fn on_schedule(&self) {}
+
+    /// Called when the entry expires and is moved to the pending wake queue
+    #[inline(always)]
+    fn on_expire(&self) {}
+
+    /// Called when the entry is removed from the wheel with
+    /// [`Wheel::cancel`](crate::Wheel::cancel)
+    #[inline(always)]
+    fn on_cancel(&self) {}
 }
 
 pub trait Queue<E: Entry<Queue = Self>> {
     fn new() -> Self;
     fn is_empty(&self) -> bool;
     fn push(&mut self, entry: E);
+
+    /// Pushes an entry onto the pending wake queue
+    ///
+    /// Queues that want a defined wake order insert the entry after every
+    /// entry with an earlier deadline, or the same deadline and a priority
+    /// that is lower or equal, so equal entries keep their insertion order.
+    /// By default the entry is pushed to the back.
+    #[inline(always)]
+    fn push_sorted(&mut self, entry: E) {
+        self.push(entry)
+    }
     fn pop(&mut self) -> Option<E>;
+    fn remove(&mut self, entry: &E) -> Option<E>;
     fn take(&mut self) -> Self;
     fn count(&self) -> usize;
+
+    /// Iterator over the entries in the queue, from front to back
+    type Iter<'a>: Iterator<Item = EntryRef<'a, E>>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_>;
+
+    #[inline]
+    fn for_each<F: FnMut(&E)>(&self, mut f: F) {
+        for entry in self.iter() {
+            f(&entry);
+        }
+    }
+
+    /// Calls `f` with the entry at the front of the queue
+    ///
+    /// The default implementation walks the queue with [`Queue::for_each`] so
+    /// implementations should override it if they can do better.
+    #[inline]
+    fn with_front<R, F: FnOnce(&E) -> R>(&self, f: F) -> Option<R> {
+        let mut f = Some(f);
+        let mut result = None;
+        self.for_each(|entry| {
+            if let Some(f) = f.take() {
+                result = Some(f(entry));
+            }
+        });
+        result
+    }
     fn next_expiring(&self) -> <E::Storage as Storage<E>>::Tick;
 }
 
+/// A view of an entry that is held in a [`Queue`]
+///
+/// Intrusive queues don't store `E` itself, so the view holds a second handle
+/// to the entry, e.g. a clone of an `Arc`, that is only reachable by reference
+/// while the queue is borrowed.
+pub struct EntryRef<'a, E> {
+    entry: E,
+    queue: PhantomData<&'a ()>,
+}
+
+impl<E> EntryRef<'_, E> {
+    #[inline(always)]
+    pub fn new(entry: E) -> Self {
+        Self {
+            entry,
+            queue: PhantomData,
+        }
+    }
+}
+
+impl<E> ops::Deref for EntryRef<'_, E> {
+    type Target = E;
+
+    #[inline(always)]
+    fn deref(&self) -> &E {
+        &self.entry
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Debug for EntryRef<'_, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.entry.fmt(f)
+    }
+}
+
+/// A [`Queue`] that can be created in a const context
+///
+/// Required by [`Wheel::new`](crate::Wheel::new) so the wheel can be placed in a `static`.
+pub trait ConstQueue<E: Entry<Queue = Self>>: Queue<E> {
+    const NEW: Self;
+}
+
 pub trait Storage<E: Entry>: Default + AsRef<[Stack<E>]> + AsMut<[Stack<E>]> {
     type Tick: Tick;
 
@@ -48,6 +168,25 @@ pub trait Storage<E: Entry>: Default + AsRef<[Stack<E>]> + AsMut<[Stack<E>]> {
     }
 }
 
+/// A [`Storage`] that can be created in a const context
+pub trait ConstStorage<E: Entry>: Storage<E> {
+    const NEW: Self;
+}
+
+impl<E: Entry> ConstStorage<E> for [Stack<E>; 4]
+where
+    E::Queue: ConstQueue<E>,
+{
+    const NEW: Self = [const { Stack::NEW }; 4];
+}
+
+impl<E: Entry> ConstStorage<E> for [Stack<E>; 8]
+where
+    E::Queue: ConstQueue<E>,
+{
+    const NEW: Self = [const { Stack::NEW }; 8];
+}
+
 impl<E: Entry> Storage<E> for [Stack<E>; 4] {
     type Tick = u32;
 
@@ -94,6 +233,7 @@ pub trait Tick
 where
     Self: Copy
         + Default
+        + Ord
         + Sized
         + ops::BitXor<Output = Self>
         + ops::Add<Output = Self>
@@ -101,7 +241,10 @@ where
 {
     type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
 
+    const ONE: Self;
+
     fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
     fn checked_sub(self, rhs: Self) -> Option<Self>;
     fn to_be(self) -> Self;
     fn to_le_bytes(self) -> Self::Bytes;
@@ -114,6 +257,8 @@ where
 impl Tick for u32 {
     type Bytes = [u8; 4];
 
+    const ONE: Self = 1;
+
     fn checked_sub(self, rhs: Self) -> Option<Self> {
         u32::checked_sub(self, rhs)
     }
@@ -122,6 +267,10 @@ impl Tick for u32 {
         u32::wrapping_add(self, rhs)
     }
 
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        u32::wrapping_sub(self, rhs)
+    }
+
     fn to_be(self) -> Self {
         u32::to_be(self)
     }
@@ -143,17 +292,15 @@ impl Tick for u32 {
     }
 
     fn elapsed_since(self, rhs: Self) -> Self {
-        if let Some(d) = self.checked_sub(rhs) {
-            d
-        } else {
-            self + (rhs - Self::MAX)
-        }
+        self.wrapping_sub(rhs)
     }
 }
 
 impl Tick for u64 {
     type Bytes = [u8; 8];
 
+    const ONE: Self = 1;
+
     fn checked_sub(self, rhs: Self) -> Option<Self> {
         u64::checked_sub(self, rhs)
     }
@@ -162,6 +309,10 @@ impl Tick for u64 {
         u64::wrapping_add(self, rhs)
     }
 
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        u64::wrapping_sub(self, rhs)
+    }
+
     fn to_be(self) -> Self {
         u64::to_be(self)
     }
@@ -183,11 +334,7 @@ impl Tick for u64 {
     }
 
     fn elapsed_since(self, rhs: Self) -> Self {
-        if let Some(d) = self.checked_sub(rhs) {
-            d
-        } else {
-            self + (rhs - Self::MAX)
-        }
+        self.wrapping_sub(rhs)
     }
 }
 
@@ -195,23 +342,72 @@ impl Tick for u64 {
 pub mod atomic {
     use super::*;
     use alloc::sync::Arc;
-    use core::{
-        sync::atomic::{AtomicBool, AtomicU64, Ordering},
-        task::Waker,
+    use core::task::Waker;
+    use intrusive_collections::{
+        intrusive_adapter, linked_list::Cursor, LinkedList, LinkedListLink,
     };
+
+    #[cfg(not(loom))]
+    use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+    #[cfg(not(loom))]
     use futures::task::AtomicWaker;
-    use intrusive_collections::{intrusive_adapter, LinkedList, LinkedListLink};
+
+    #[cfg(loom)]
+    use self::model::AtomicWaker;
+    #[cfg(loom)]
+    use loom::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
     intrusive_adapter!(pub Adapter = ArcEntry: Entry { link: LinkedListLink });
 
     pub type ArcEntry = Arc<Entry>;
 
+    /// The lifecycle of an [`Entry`]
+    ///
+    /// ```text
+    /// Idle ---> Scheduled ---> Pending ---> Fired ---> Idle
+    ///             |               |
+    ///             +--> Cancelled <+
+    /// ```
+    ///
+    /// A fired entry may also be scheduled again directly, while a cancelled
+    /// entry is only scheduled again by [`Entry::should_register`]. Inserting
+    /// a cancelled entry into a wheel leaves it cancelled, so it is dropped
+    /// when it expires without being woken.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum State {
+        /// The entry has not been scheduled, or its expiration was consumed
+        /// with [`Entry::take_expired`]
+        Idle,
+        /// The entry is waiting in a wheel
+        Scheduled,
+        /// The entry expired and is waiting to be woken
+        Pending,
+        /// The entry was woken
+        Fired,
+        /// The entry was cancelled before it fired
+        Cancelled,
+    }
+
+    impl State {
+        fn from_u8(value: u8) -> Self {
+            match value {
+                0 => Self::Idle,
+                1 => Self::Scheduled,
+                2 => Self::Pending,
+                3 => Self::Fired,
+                4 => Self::Cancelled,
+                _ => unreachable!("invalid entry state {}", value),
+            }
+        }
+    }
+
     #[derive(Debug)]
     pub struct Entry {
         waker: AtomicWaker,
-        expired: AtomicBool,
-        registered: AtomicBool,
+        state: AtomicU8,
         delay: u64,
+        priority: u32,
         start_tick: AtomicU64,
         link: LinkedListLink,
     }
@@ -225,35 +421,120 @@ pub mod atomic {
 
     impl Entry {
         pub fn new(delay: u64) -> Arc<Self> {
+            Self::with_priority(delay, 0)
+        }
+
+        /// Creates an entry that wakes before entries with a higher priority
+        /// that expire on the same tick
+        pub fn with_priority(delay: u64, priority: u32) -> Arc<Self> {
             Arc::new(Self {
                 waker: AtomicWaker::new(),
-                expired: AtomicBool::new(false),
-                registered: AtomicBool::new(false),
+                state: AtomicU8::new(State::Idle as u8),
                 delay,
+                priority,
                 start_tick: AtomicU64::new(0),
                 link: LinkedListLink::new(),
             })
         }
 
-        pub fn wake(&self) {
-            self.expired.store(true, Ordering::SeqCst);
-            self.registered.store(false, Ordering::SeqCst);
+        /// Returns the current state of the entry
+        pub fn state(&self) -> State {
+            // pairs with the release in `wake`
+            State::from_u8(self.state.load(Ordering::Acquire))
+        }
+
+        /// Moves the entry from one of the `from` states to `to`
+        ///
+        /// Returns the previous state, or the current state if it wasn't one
+        /// of the `from` states.
+        ///
+        /// All of the transitions are made on the one atomic, so its
+        /// modification order alone decides which of two racing transitions
+        /// wins. `ordering` is only needed to publish other memory along with
+        /// the new state.
+        fn transition(
+            &self,
+            from: &[State],
+            to: State,
+            ordering: Ordering,
+        ) -> Result<State, State> {
+            let mut current = State::from_u8(self.state.load(Ordering::Relaxed));
+            loop {
+                if !from.contains(&current) {
+                    return Err(current);
+                }
 
-            if let Some(waker) = self.waker.take() {
-                waker.wake();
+                match self.state.compare_exchange_weak(
+                    current as u8,
+                    to as u8,
+                    ordering,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Ok(current),
+                    Err(actual) => current = State::from_u8(actual),
+                }
             }
         }
 
+        /// Marks a scheduled entry as fired and wakes the registered waker
+        ///
+        /// Entries that were cancelled are not woken.
+        pub fn wake(&self) {
+            // releases everything the timer did up to this point to the task
+            // that observes the entry as fired
+            if self
+                .transition(
+                    &[State::Scheduled, State::Pending],
+                    State::Fired,
+                    Ordering::Release,
+                )
+                .is_ok()
+            {
+                if let Some(waker) = self.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+
+        /// Returns `true` if the entry isn't scheduled, in which case the
+        /// caller is responsible for inserting it into a wheel
         pub fn should_register(&self) -> bool {
-            !self.registered.swap(true, Ordering::SeqCst)
+            self.transition(
+                &[State::Idle, State::Cancelled],
+                State::Scheduled,
+                Ordering::Relaxed,
+            )
+            .is_ok()
         }
 
-        pub fn cancel(&self) {
+        /// Cancels the entry if it hasn't fired yet and drops the registered waker
+        ///
+        /// Returns `true` if the entry hasn't fired, in which case no waker
+        /// will be woken by the entry until it is scheduled again.
+        pub fn cancel(&self) -> bool {
+            let cancelled = self
+                .transition(
+                    &[
+                        State::Idle,
+                        State::Scheduled,
+                        State::Pending,
+                        State::Cancelled,
+                    ],
+                    State::Cancelled,
+                    Ordering::Relaxed,
+                )
+                .is_ok();
             self.waker.take();
+            cancelled
         }
 
+        /// Returns `true` if the entry fired, resetting it to [`State::Idle`]
+        ///
+        /// To avoid missing a wake-up, the waker should be registered with
+        /// [`Entry::register`] before calling this.
         pub fn take_expired(&self) -> bool {
-            self.expired.swap(false, Ordering::SeqCst)
+            self.transition(&[State::Fired], State::Idle, Ordering::Acquire)
+                .is_ok()
         }
 
         pub fn register(&self, waker: &Waker) {
@@ -261,7 +542,24 @@ pub mod atomic {
         }
 
         fn start_tick(&self) -> u64 {
-            self.start_tick.load(Ordering::SeqCst)
+            // only accessed by the owner of the wheel
+            self.start_tick.load(Ordering::Relaxed)
+        }
+
+        fn deadline(&self) -> u64 {
+            self.start_tick().wrapping_add(self.delay)
+        }
+
+        /// Returns `true` if the entry should wake after `other`
+        fn wakes_after(&self, other: &Entry) -> bool {
+            let deadline = self.deadline();
+            let other_deadline = other.deadline();
+
+            // compare the deadlines relative to each other to handle wrapping
+            match (deadline.wrapping_sub(other_deadline) as i64).cmp(&0) {
+                core::cmp::Ordering::Equal => self.priority > other.priority,
+                ordering => ordering == core::cmp::Ordering::Greater,
+            }
         }
     }
 
@@ -278,7 +576,38 @@ pub mod atomic {
         }
 
         fn set_start_tick(&mut self, tick: u64) {
-            self.start_tick.store(tick, Ordering::SeqCst);
+            self.start_tick.store(tick, Ordering::Relaxed);
+        }
+
+        fn is_scheduled(&self) -> bool {
+            self.link.is_linked()
+        }
+
+        fn priority(&self) -> u32 {
+            self.priority
+        }
+
+        fn on_schedule(&self) {
+            // a cancel that raced with the insert, e.g. after `should_register`,
+            // isn't overwritten
+            let _ = self.transition(
+                &[State::Idle, State::Scheduled, State::Pending, State::Fired],
+                State::Scheduled,
+                Ordering::Relaxed,
+            );
+        }
+
+        fn on_expire(&self) {
+            // an entry that was cancelled while still in the wheel stays cancelled
+            let _ = self.transition(&[State::Scheduled], State::Pending, Ordering::Relaxed);
+        }
+
+        fn on_cancel(&self) {
+            let _ = self.transition(
+                &[State::Scheduled, State::Pending],
+                State::Cancelled,
+                Ordering::Relaxed,
+            );
         }
     }
 
@@ -288,6 +617,388 @@ pub mod atomic {
         }
     }
 
+    /// A model of [`futures::task::AtomicWaker`] that loom can explore
+    ///
+    /// Mirrors the state machine of the real implementation, including its
+    /// orderings, so the register/take handoff is checked along with the
+    /// entry's own state.
+    #[cfg(loom)]
+    mod model {
+        use core::{fmt, task::Waker};
+        use loom::{
+            cell::UnsafeCell,
+            sync::atomic::{
+                AtomicUsize,
+                Ordering::{AcqRel, Acquire, Release},
+            },
+        };
+
+        const WAITING: usize = 0;
+        const REGISTERING: usize = 0b01;
+        const WAKING: usize = 0b10;
+
+        pub struct AtomicWaker {
+            state: AtomicUsize,
+            waker: UnsafeCell<Option<Waker>>,
+        }
+
+        impl AtomicWaker {
+            pub fn new() -> Self {
+                Self {
+                    state: AtomicUsize::new(WAITING),
+                    waker: UnsafeCell::new(None),
+                }
+            }
+
+            pub fn register(&self, waker: &Waker) {
+                match self
+                    .state
+                    .compare_exchange(WAITING, REGISTERING, Acquire, Acquire)
+                    .unwrap_or_else(|state| state)
+                {
+                    WAITING => {
+                        // the registering bit gives exclusive access to the cell
+                        self.waker.with_mut(|cell| unsafe {
+                            match &*cell {
+                                Some(old) if old.will_wake(waker) => {}
+                                _ => *cell = Some(waker.clone()),
+                            }
+                        });
+
+                        if let Err(state) =
+                            self.state
+                                .compare_exchange(REGISTERING, WAITING, AcqRel, Acquire)
+                        {
+                            // a concurrent `take` missed the new waker so it's
+                            // woken here instead
+                            assert_eq!(state, REGISTERING | WAKING);
+                            let waker = self
+                                .waker
+                                .with_mut(|cell| unsafe { (*cell).take() })
+                                .unwrap();
+                            self.state.swap(WAITING, AcqRel);
+                            waker.wake();
+                        }
+                    }
+                    WAKING => waker.wake_by_ref(),
+                    state => assert!(state == REGISTERING || state == REGISTERING | WAKING),
+                }
+            }
+
+            pub fn take(&self) -> Option<Waker> {
+                match self.state.fetch_or(WAKING, AcqRel) {
+                    WAITING => {
+                        let waker = self.waker.with_mut(|cell| unsafe { (*cell).take() });
+                        let state = self.state.swap(WAITING, Release);
+                        assert_eq!(state, WAKING);
+                        waker
+                    }
+                    // the registering thread, or the other `take`, will see
+                    // the waking bit
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Debug for AtomicWaker {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_struct("AtomicWaker").finish_non_exhaustive()
+            }
+        }
+    }
+
+    /// Model checks the handoff between the thread that drives the wheel and
+    /// the task that owns an entry
+    ///
+    /// Run with `RUSTFLAGS="--cfg loom" cargo test --release --features loom --lib loom`.
+    #[cfg(all(test, loom))]
+    mod loom_tests {
+        use super::*;
+        use crate::Wheel;
+        use alloc::{task::Wake, vec::Vec};
+        use loom::{
+            sync::{atomic::AtomicUsize, Mutex},
+            thread,
+        };
+
+        #[derive(Default)]
+        struct CountingWaker(AtomicUsize);
+
+        impl CountingWaker {
+            fn count(&self) -> usize {
+                self.0.load(Ordering::Relaxed)
+            }
+        }
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        /// Advances the wheel by a tick and wakes whatever expired outside of the lock
+        fn drive(wheel: &Mutex<Wheel<ArcEntry>>) {
+            let mut expired = Vec::new();
+            {
+                let mut wheel = wheel.lock().unwrap();
+                let now = wheel.ticks() + 1;
+                wheel.poll(now, |entry| expired.push(entry));
+            }
+            for entry in expired {
+                entry.wake();
+            }
+        }
+
+        /// Inserts the entry if it isn't already scheduled
+        fn schedule(wheel: &Mutex<Wheel<ArcEntry>>, entry: &ArcEntry) {
+            if entry.should_register() {
+                wheel.lock().unwrap().insert(entry.clone());
+            }
+        }
+
+        /// Spawns a thread with enough stack to hold a wheel
+        fn spawn<F: FnOnce() + Send + 'static>(f: F) -> thread::JoinHandle<()> {
+            thread::Builder::new().stack_size(1 << 20).spawn(f).unwrap()
+        }
+
+        /// Runs a model on a thread that has room for a wheel on its stack
+        fn model_wheel<F: Fn() + Sync + Send + 'static>(f: F) {
+            let f = Arc::new(f);
+            loom::model(move || {
+                let f = f.clone();
+                spawn(move || f()).join().unwrap();
+            });
+        }
+
+        /// Mirrors the `Drop` of a sleep future
+        fn release(wheel: &Mutex<Wheel<ArcEntry>>, entry: &ArcEntry) -> bool {
+            wheel.lock().unwrap().cancel(entry);
+            entry.cancel()
+        }
+
+        #[test]
+        fn wake_register_test() {
+            loom::model(|| {
+                let entry = Entry::new(1);
+                assert!(entry.should_register());
+
+                let timer = {
+                    let entry = entry.clone();
+                    thread::spawn(move || entry.wake())
+                };
+
+                let counter = Arc::new(CountingWaker::default());
+                let waker = Waker::from(counter.clone());
+                entry.register(&waker);
+                let expired = entry.take_expired();
+
+                timer.join().unwrap();
+
+                // the task either saw the expiration or is woken to look again
+                if !expired {
+                    assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+                    assert!(entry.take_expired());
+                }
+            });
+        }
+
+        #[test]
+        fn wake_cancel_test() {
+            loom::model(|| {
+                let entry = Entry::new(1);
+                assert!(entry.should_register());
+
+                let counter = Arc::new(CountingWaker::default());
+                entry.register(&Waker::from(counter.clone()));
+
+                let timer = {
+                    let entry = entry.clone();
+                    thread::spawn(move || entry.wake())
+                };
+
+                let cancelled = entry.cancel();
+                timer.join().unwrap();
+
+                if cancelled {
+                    assert_eq!(entry.state(), State::Cancelled);
+                    assert_eq!(counter.count(), 0);
+                } else {
+                    assert_eq!(entry.state(), State::Fired);
+                    assert!(counter.count() <= 1);
+                }
+            });
+        }
+
+        #[test]
+        fn repoll_test() {
+            loom::model(|| {
+                let entry = Entry::new(1);
+                assert!(entry.should_register());
+
+                let timer = {
+                    let entry = entry.clone();
+                    thread::spawn(move || entry.wake())
+                };
+
+                // the task is polled twice, with a different waker each time
+                let first = Arc::new(CountingWaker::default());
+                entry.register(&Waker::from(first.clone()));
+                let mut expired = entry.take_expired();
+
+                let second = Arc::new(CountingWaker::default());
+                if !expired {
+                    entry.register(&Waker::from(second.clone()));
+                    expired = entry.take_expired();
+                }
+
+                timer.join().unwrap();
+
+                // the most recent waker must be woken if the task went back to sleep
+                if !expired {
+                    assert_eq!(second.count(), 1);
+                    assert!(entry.take_expired());
+                }
+                // registering while the waker is being taken wakes the new
+                // waker as well, so both may be woken, but never twice
+                assert!(first.count() <= 1);
+                assert!(second.count() <= 1);
+            });
+        }
+
+        #[test]
+        fn cancel_register_test() {
+            loom::model(|| {
+                let entry = Entry::new(1);
+                assert!(entry.should_register());
+
+                let first = Arc::new(CountingWaker::default());
+                entry.register(&Waker::from(first.clone()));
+
+                let timer = {
+                    let entry = entry.clone();
+                    thread::spawn(move || entry.wake())
+                };
+
+                let cancelled = entry.cancel();
+
+                // a waker registered after the entry was cancelled is never woken
+                let second = Arc::new(CountingWaker::default());
+                entry.register(&Waker::from(second.clone()));
+
+                timer.join().unwrap();
+
+                if cancelled {
+                    assert_eq!(first.count(), 0);
+                    assert_eq!(second.count(), 0);
+                    assert!(!entry.take_expired());
+                }
+            });
+        }
+
+        #[test]
+        fn schedule_cancel_test() {
+            model_wheel(|| {
+                let wheel = Arc::new(Mutex::new(Wheel::<ArcEntry>::default()));
+                let entry = Entry::new(1);
+                let counter = Arc::new(CountingWaker::default());
+                entry.register(&Waker::from(counter.clone()));
+                assert!(entry.should_register());
+
+                let task = {
+                    let wheel = wheel.clone();
+                    let entry = entry.clone();
+                    spawn(move || wheel.lock().unwrap().insert(entry))
+                };
+
+                // the entry is cancelled between registering and being inserted,
+                // or after it was inserted
+                assert!(entry.cancel());
+                task.join().unwrap();
+                drive(&wheel);
+
+                assert_eq!(counter.count(), 0);
+                assert_eq!(entry.state(), State::Cancelled);
+                assert!(!entry.take_expired());
+                assert!(wheel.lock().unwrap().is_empty());
+            });
+        }
+
+        #[test]
+        fn wheel_wake_test() {
+            model_wheel(|| {
+                let wheel = Arc::new(Mutex::new(Wheel::<ArcEntry>::default()));
+                let entry = Entry::new(1);
+                let counter = Arc::new(CountingWaker::default());
+
+                // the task registers before scheduling, like the first poll of a sleep
+                entry.register(&Waker::from(counter.clone()));
+                schedule(&wheel, &entry);
+
+                let timer = {
+                    let wheel = wheel.clone();
+                    spawn(move || drive(&wheel))
+                };
+
+                let expired = entry.take_expired();
+                timer.join().unwrap();
+
+                if !expired {
+                    assert_eq!(counter.count(), 1);
+                    assert!(entry.take_expired());
+                }
+                assert!(wheel.lock().unwrap().is_empty());
+            });
+        }
+
+        #[test]
+        fn wheel_drop_test() {
+            model_wheel(|| {
+                let wheel = Arc::new(Mutex::new(Wheel::<ArcEntry>::default()));
+                let entry = Entry::new(1);
+                let counter = Arc::new(CountingWaker::default());
+
+                entry.register(&Waker::from(counter.clone()));
+                schedule(&wheel, &entry);
+
+                let timer = {
+                    let wheel = wheel.clone();
+                    spawn(move || drive(&wheel))
+                };
+
+                // the sleep is dropped while the timer may be firing it
+                let cancelled = release(&wheel, &entry);
+                timer.join().unwrap();
+
+                if cancelled {
+                    assert_eq!(counter.count(), 0);
+                    assert_eq!(entry.state(), State::Cancelled);
+                } else {
+                    assert_eq!(entry.state(), State::Fired);
+                }
+
+                // neither the wheel nor the timer holds on to the entry
+                assert!(wheel.lock().unwrap().is_empty());
+                assert_eq!(Arc::strong_count(&entry), 1);
+                drop(entry);
+            });
+        }
+    }
+
+    /// Iterator over the entries in a [`LinkedList`] of [`Entry`]s
+    pub struct Iter<'a> {
+        cursor: Cursor<'a, Adapter>,
+    }
+
+    impl<'a> Iterator for Iter<'a> {
+        type Item = EntryRef<'a, ArcEntry>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let entry = self.cursor.clone_pointer()?;
+            self.cursor.move_next();
+            Some(EntryRef::new(entry))
+        }
+    }
+
     impl Queue<ArcEntry> for LinkedList<Adapter> {
         fn new() -> Self {
             LinkedList::new(Adapter::new())
@@ -301,10 +1012,45 @@ pub mod atomic {
             self.push_back(entry);
         }
 
+        fn push_sorted(&mut self, entry: ArcEntry) {
+            // entries usually arrive in order so check the back first
+            match self.back().get() {
+                Some(back) if back.wakes_after(&entry) => {}
+                _ => return self.push_back(entry),
+            }
+
+            let mut cursor = self.front_mut();
+            while let Some(candidate) = cursor.get() {
+                if candidate.wakes_after(&entry) {
+                    break;
+                }
+                cursor.move_next();
+            }
+
+            // the back wakes after the entry so the cursor stops on an element
+            cursor.insert_before(entry);
+        }
+
         fn pop(&mut self) -> Option<ArcEntry> {
             self.pop_front()
         }
 
+        fn remove(&mut self, entry: &ArcEntry) -> Option<ArcEntry> {
+            if !entry.link.is_linked() {
+                return None;
+            }
+
+            let mut cursor = self.front_mut();
+            while let Some(current) = cursor.get() {
+                if core::ptr::eq(current, &**entry) {
+                    return cursor.remove();
+                }
+                cursor.move_next();
+            }
+
+            None
+        }
+
         fn take(&mut self) -> Self {
             LinkedList::take(self)
         }
@@ -313,6 +1059,19 @@ pub mod atomic {
             self.iter().count()
         }
 
+        type Iter<'a> = Iter<'a>;
+
+        fn iter(&self) -> Iter<'_> {
+            Iter {
+                cursor: self.front(),
+            }
+        }
+
+        fn with_front<R, F: FnOnce(&ArcEntry) -> R>(&self, f: F) -> Option<R> {
+            let entry = self.front().clone_pointer()?;
+            Some(f(&entry))
+        }
+
         fn next_expiring(&self) -> u64 {
             self.iter()
                 .map(|e| {
@@ -328,3 +1087,295 @@ pub mod atomic {
         }
     }
 }
+
+/// Entries with fixed addresses that don't require an allocator
+///
+/// Each [`Entry`] is expected to live in `'static` storage, e.g. a `static`
+/// item, and the wheel links them together through a pointer embedded in the
+/// entry. Along with [`Wheel::new`](crate::Wheel::new) this allows the wheel
+/// itself to be placed in a `static` on targets without `alloc`.
+///
+/// ```
+/// use timewarp::{entry::fixed, Wheel};
+///
+/// static TIMER: fixed::Entry = fixed::Entry::new(100);
+///
+/// let mut wheel = Wheel::<fixed::StaticEntry>::new();
+/// wheel.insert(&TIMER);
+/// assert_eq!(wheel.skip(), Some(100));
+/// assert_eq!(wheel.wake(|entry| assert!(core::ptr::eq(entry, &TIMER))), 1);
+/// ```
+pub mod fixed {
+    use super::*;
+    use crate::Error;
+    use core::{
+        ptr,
+        sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, Ordering},
+    };
+
+    pub type StaticEntry = &'static Entry;
+
+    /// A timer entry with a `u32` tick range
+    ///
+    /// Entries that expire on the same tick are woken in order of their
+    /// priority and then in the order they were inserted.
+    ///
+    /// The wheel only modifies the entry while it has exclusive access to
+    /// itself so the fields use relaxed atomics purely to allow the entry to be
+    /// shared in a `static`.
+    #[derive(Debug)]
+    pub struct Entry {
+        delay: AtomicU32,
+        priority: u32,
+        start_tick: AtomicU32,
+        next: AtomicPtr<Entry>,
+        linked: AtomicBool,
+    }
+
+    impl Entry {
+        pub const fn new(delay: u32) -> Self {
+            Self::with_priority(delay, 0)
+        }
+
+        /// Creates an entry that wakes before entries with a higher priority
+        /// value that expire on the same tick
+        pub const fn with_priority(delay: u32, priority: u32) -> Self {
+            Self {
+                delay: AtomicU32::new(delay),
+                priority,
+                start_tick: AtomicU32::new(0),
+                next: AtomicPtr::new(ptr::null_mut()),
+                linked: AtomicBool::new(false),
+            }
+        }
+
+        /// Returns `true` if the entry is currently held in a wheel
+        pub fn is_scheduled(&self) -> bool {
+            self.linked.load(Ordering::Relaxed)
+        }
+
+        /// Changes the delay used the next time the entry is inserted
+        ///
+        /// Returns [`Error::AlreadyScheduled`] if the entry is held in a wheel,
+        /// since the wheel locates the entry by its current deadline. The
+        /// check isn't atomic with an insert of the same entry, so the delay
+        /// should be changed from the context that inserts it, e.g. inside
+        /// `SharedWheel::with`.
+        pub fn set_delay(&self, delay: u32) -> Result<(), Error> {
+            if self.is_scheduled() {
+                return Err(Error::AlreadyScheduled);
+            }
+
+            self.delay.store(delay, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn next(&self) -> Option<StaticEntry> {
+            // only `'static` entries are ever linked
+            unsafe { self.next.load(Ordering::Relaxed).as_ref() }
+        }
+
+        fn deadline(&self) -> u32 {
+            let start_tick = self.start_tick.load(Ordering::Relaxed);
+            start_tick.wrapping_add(self.delay.load(Ordering::Relaxed))
+        }
+
+        /// Returns `true` if the entry should wake after `other`
+        fn wakes_after(&self, other: &Entry) -> bool {
+            let deadline = self.deadline();
+            let other_deadline = other.deadline();
+
+            // compare the deadlines relative to each other to handle wrapping
+            match (deadline.wrapping_sub(other_deadline) as i32).cmp(&0) {
+                core::cmp::Ordering::Equal => self.priority > other.priority,
+                ordering => ordering == core::cmp::Ordering::Greater,
+            }
+        }
+
+        fn set_next(&self, next: Option<StaticEntry>) {
+            let next = next.map_or(ptr::null_mut(), |next| next as *const Entry as *mut Entry);
+            self.next.store(next, Ordering::Relaxed);
+        }
+    }
+
+    impl super::Entry for &'static Entry {
+        type Queue = List;
+        type Storage = [Stack<Self>; 4];
+
+        fn delay(&self) -> u32 {
+            self.delay.load(Ordering::Relaxed)
+        }
+
+        fn start_tick(&self) -> u32 {
+            self.start_tick.load(Ordering::Relaxed)
+        }
+
+        fn set_start_tick(&mut self, tick: u32) {
+            self.start_tick.store(tick, Ordering::Relaxed);
+        }
+
+        fn is_scheduled(&self) -> bool {
+            Entry::is_scheduled(self)
+        }
+
+        fn priority(&self) -> u32 {
+            self.priority
+        }
+    }
+
+    /// Iterator over the entries in a [`List`]
+    pub struct Iter<'a> {
+        current: Option<StaticEntry>,
+        list: PhantomData<&'a List>,
+    }
+
+    impl<'a> Iterator for Iter<'a> {
+        type Item = EntryRef<'a, StaticEntry>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let entry = self.current?;
+            self.current = entry.next();
+            Some(EntryRef::new(entry))
+        }
+    }
+
+    /// An intrusive FIFO of [`Entry`]s
+    #[derive(Debug, Default)]
+    pub struct List {
+        head: Option<StaticEntry>,
+        tail: Option<StaticEntry>,
+    }
+
+    impl ConstQueue<StaticEntry> for List {
+        const NEW: Self = Self {
+            head: None,
+            tail: None,
+        };
+    }
+
+    impl Queue<StaticEntry> for List {
+        fn new() -> Self {
+            Self::NEW
+        }
+
+        fn is_empty(&self) -> bool {
+            self.head.is_none()
+        }
+
+        fn push(&mut self, entry: StaticEntry) {
+            debug_assert!(!entry.linked.load(Ordering::Relaxed));
+            entry.linked.store(true, Ordering::Relaxed);
+            entry.set_next(None);
+
+            match self.tail {
+                Some(tail) => tail.set_next(Some(entry)),
+                None => self.head = Some(entry),
+            }
+            self.tail = Some(entry);
+        }
+
+        fn push_sorted(&mut self, entry: StaticEntry) {
+            // entries usually arrive in order so check the back first
+            match self.tail {
+                Some(tail) if tail.wakes_after(entry) => {}
+                _ => return self.push(entry),
+            }
+
+            debug_assert!(!entry.linked.load(Ordering::Relaxed));
+            entry.linked.store(true, Ordering::Relaxed);
+
+            let mut prev: Option<StaticEntry> = None;
+            let mut current = self.head;
+
+            while let Some(candidate) = current {
+                if candidate.wakes_after(entry) {
+                    break;
+                }
+                prev = current;
+                current = candidate.next();
+            }
+
+            // the tail wakes after the entry so there's always a successor
+            entry.set_next(current);
+            match prev {
+                Some(prev) => prev.set_next(Some(entry)),
+                None => self.head = Some(entry),
+            }
+        }
+
+        fn pop(&mut self) -> Option<StaticEntry> {
+            let entry = self.head?;
+            self.head = entry.next();
+            if self.head.is_none() {
+                self.tail = None;
+            }
+
+            entry.set_next(None);
+            entry.linked.store(false, Ordering::Relaxed);
+            Some(entry)
+        }
+
+        fn remove(&mut self, entry: &StaticEntry) -> Option<StaticEntry> {
+            if !entry.linked.load(Ordering::Relaxed) {
+                return None;
+            }
+
+            let mut prev: Option<StaticEntry> = None;
+            let mut current = self.head;
+
+            while let Some(candidate) = current {
+                if ptr::eq(candidate, *entry) {
+                    let next = candidate.next();
+                    match prev {
+                        Some(prev) => prev.set_next(next),
+                        None => self.head = next,
+                    }
+                    if next.is_none() {
+                        self.tail = prev;
+                    }
+
+                    candidate.set_next(None);
+                    candidate.linked.store(false, Ordering::Relaxed);
+                    return Some(candidate);
+                }
+
+                prev = current;
+                current = candidate.next();
+            }
+
+            None
+        }
+
+        fn take(&mut self) -> Self {
+            core::mem::replace(self, Self::NEW)
+        }
+
+        fn count(&self) -> usize {
+            let mut count = 0;
+            self.for_each(|_| count += 1);
+            count
+        }
+
+        type Iter<'a> = Iter<'a>;
+
+        fn iter(&self) -> Iter<'_> {
+            Iter {
+                current: self.head,
+                list: PhantomData,
+            }
+        }
+
+        fn with_front<R, F: FnOnce(&StaticEntry) -> R>(&self, f: F) -> Option<R> {
+            self.head.as_ref().map(f)
+        }
+
+        fn next_expiring(&self) -> u32 {
+            let mut next = None;
+            self.for_each(|entry| {
+                let deadline = super::Entry::deadline(entry);
+                next = Some(next.map_or(deadline, |next: u32| next.min(deadline)));
+            });
+            next.unwrap_or(0)
+        }
+    }
+}
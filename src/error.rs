@@ -0,0 +1,32 @@
+use core::fmt;
+
+/// Errors returned by the fallible [`Wheel`](crate::Wheel) operations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The entry's deadline is before the wheel's current tick
+    DeadlineInPast,
+    /// The entry's deadline lies past the end of the tick range
+    DeadlineOutOfRange,
+    /// The entry is already held in a wheel
+    AlreadyScheduled,
+    /// The entry is not scheduled in this wheel
+    NotScheduled,
+    /// The target tick is before the wheel's current tick
+    TimeWentBackwards,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::DeadlineInPast => "the deadline is before the current tick",
+            Self::DeadlineOutOfRange => "the deadline lies past the end of the tick range",
+            Self::AlreadyScheduled => "the entry is already scheduled",
+            Self::NotScheduled => "the entry is not scheduled in this wheel",
+            Self::TimeWentBackwards => "the target tick is before the current tick",
+        };
+        f.write_str(message)
+    }
+}
+
+impl core::error::Error for Error {}
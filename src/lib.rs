@@ -6,11 +6,29 @@ extern crate alloc;
 #[cfg(test)]
 extern crate std;
 
+#[cfg(all(loom, not(feature = "loom")))]
+compile_error!("building with `--cfg loom` requires the `loom` feature");
+
 mod bitset;
+mod error;
+mod observer;
+#[cfg(feature = "alloc")]
+mod snapshot;
 mod stack;
 mod wheel;
 
+#[cfg(feature = "critical-section")]
+pub mod critical;
 pub mod entry;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 pub use entry::Entry;
-pub use wheel::Wheel;
+pub use error::Error;
+pub use observer::WheelObserver;
+pub use wheel::{Expired, Iter, Poll, StackStats, Stats, WakeInfo, Wheel};
+
+#[cfg(feature = "alloc")]
+pub use snapshot::{Record, Snapshot};
@@ -0,0 +1,47 @@
+use crate::entry::Tick;
+
+/// Observes the activity of a [`Wheel`](crate::Wheel)
+///
+/// All of the methods default to doing nothing so implementations only need
+/// to override the events they are interested in. The `()` implementation is
+/// used by default and compiles away entirely.
+///
+/// Stack indexes start at `0` for the finest resolution. A stack of `None`
+/// indicates the entry was placed in the pending wake queue. A stack equal to
+/// the number of stacks in the wheel indicates the overflow list, which holds
+/// the entries that expire after the tick range wraps around.
+pub trait WheelObserver<T: Tick> {
+    /// Called when an entry is inserted into the wheel
+    #[inline(always)]
+    fn on_insert(&mut self, stack: Option<usize>, now: T, deadline: T) {
+        let _ = (stack, now, deadline);
+    }
+
+    /// Called when an entry is moved out of a higher stack and re-inserted
+    #[inline(always)]
+    fn on_cascade(&mut self, from: usize, to: Option<usize>, now: T, deadline: T) {
+        let _ = (from, to, now, deadline);
+    }
+
+    /// Called when an expired entry is woken
+    ///
+    /// The lateness of the entry can be computed with `now - deadline`.
+    #[inline(always)]
+    fn on_wake(&mut self, now: T, deadline: T) {
+        let _ = (now, deadline);
+    }
+
+    /// Called when an entry is cancelled
+    #[inline(always)]
+    fn on_cancel(&mut self, stack: Option<usize>, now: T, deadline: T) {
+        let _ = (stack, now, deadline);
+    }
+
+    /// Called each time the wheel advances from one tick to another
+    #[inline(always)]
+    fn on_advance(&mut self, from: T, to: T) {
+        let _ = (from, to);
+    }
+}
+
+impl<T: Tick> WheelObserver<T> for () {}
@@ -0,0 +1,308 @@
+//! A deterministic, single-threaded simulation runtime
+//!
+//! Tasks are driven by a virtual clock backed by a [`Wheel`]. When none of
+//! the tasks can make progress, the clock jumps straight to the next
+//! populated slot in the wheel instead of waiting, so hours of virtual time
+//! can be simulated in milliseconds. Tasks that become runnable at the same
+//! time are polled in an order derived from the seed, which makes failures
+//! reproducible by re-running with the same seed.
+
+use crate::{
+    entry::atomic::{self, ArcEntry},
+    Wheel,
+};
+use alloc::{boxed::Box, rc::Rc, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// A simulation that owns a set of tasks and the virtual clock that drives them
+pub struct Sim {
+    shared: Rc<RefCell<Shared>>,
+    tasks: Vec<Task>,
+    rng: Rng,
+}
+
+struct Shared {
+    wheel: Wheel<ArcEntry>,
+    spawned: Vec<BoxFuture>,
+}
+
+struct Task {
+    future: BoxFuture,
+    waker: Arc<TaskWaker>,
+}
+
+#[derive(Default)]
+struct TaskWaker {
+    woken: AtomicBool,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+    }
+}
+
+impl Sim {
+    /// Creates a simulation that orders runnable tasks using `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            shared: Rc::new(RefCell::new(Shared {
+                wheel: Wheel::default(),
+                spawned: Vec::new(),
+            })),
+            tasks: Vec::new(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Returns a handle for spawning tasks and reading the clock
+    pub fn handle(&self) -> Handle {
+        Handle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Spawns a task onto the simulation
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, future: F) {
+        self.handle().spawn(future)
+    }
+
+    /// Returns the current virtual time in ticks
+    pub fn now(&self) -> u64 {
+        self.handle().now()
+    }
+
+    /// Runs the simulation until all of the tasks have completed or are
+    /// blocked on something other than a timer
+    ///
+    /// Returns the virtual time at which the simulation stopped.
+    pub fn run(&mut self) -> u64 {
+        let mut runnable = Vec::new();
+
+        loop {
+            self.poll_runnable(&mut runnable);
+
+            if !runnable.is_empty() {
+                continue;
+            }
+
+            let mut shared = self.shared.borrow_mut();
+            if shared.wheel.skip().is_none() {
+                return shared.wheel.ticks();
+            }
+            shared.wheel.wake(atomic::wake);
+        }
+    }
+
+    fn poll_runnable(&mut self, runnable: &mut Vec<usize>) {
+        let spawned = core::mem::take(&mut self.shared.borrow_mut().spawned);
+        for future in spawned {
+            let waker = Arc::new(TaskWaker::default());
+            waker.woken.store(true, Ordering::Relaxed);
+            self.tasks.push(Task { future, waker });
+        }
+
+        runnable.clear();
+        runnable.extend(
+            self.tasks
+                .iter()
+                .enumerate()
+                .filter(|(_, task)| task.waker.woken.swap(false, Ordering::Acquire))
+                .map(|(index, _)| index),
+        );
+
+        self.rng.shuffle(runnable);
+
+        let mut completed = Vec::new();
+
+        for index in runnable.iter().copied() {
+            let task = &mut self.tasks[index];
+            let waker = Waker::from(task.waker.clone());
+            let mut cx = Context::from_waker(&waker);
+            if task.future.as_mut().poll(&mut cx).is_ready() {
+                completed.push(index);
+            }
+        }
+
+        // remove from the back so the remaining indexes stay valid
+        completed.sort_unstable();
+        for index in completed.into_iter().rev() {
+            self.tasks.swap_remove(index);
+        }
+    }
+}
+
+/// A handle to a running [`Sim`]
+#[derive(Clone)]
+pub struct Handle {
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Handle {
+    /// Spawns a task onto the simulation
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, future: F) {
+        self.shared.borrow_mut().spawned.push(Box::pin(future));
+    }
+
+    /// Returns the current virtual time in ticks
+    pub fn now(&self) -> u64 {
+        self.shared.borrow().wheel.ticks()
+    }
+
+    /// Returns a future that completes after `ticks` of virtual time
+    pub fn sleep(&self, ticks: u64) -> Sleep {
+        Sleep {
+            entry: atomic::Entry::new(ticks),
+            handle: self.clone(),
+        }
+    }
+}
+
+/// A future that completes after a number of ticks of virtual time
+///
+/// Created by [`Handle::sleep`].
+pub struct Sleep {
+    entry: ArcEntry,
+    handle: Handle,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        self.entry.register(cx.waker());
+
+        if self.entry.take_expired() {
+            return Poll::Ready(());
+        }
+
+        if self.entry.should_register() {
+            self.handle
+                .shared
+                .borrow_mut()
+                .wheel
+                .insert(self.entry.clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        self.handle.shared.borrow_mut().wheel.cancel(&self.entry);
+        self.entry.cancel();
+    }
+}
+
+/// xorshift64* generator used to order runnable tasks
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Self(seed ^ 0x9e37_79b9_7f4a_7c15 | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.0 = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            values.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const HOUR: u64 = 3_600_000_000_000;
+
+    fn trace(seed: u64) -> (u64, Vec<(u64, usize)>) {
+        let mut sim = Sim::new(seed);
+        let events = Rc::new(RefCell::new(Vec::new()));
+
+        for id in 0..8 {
+            let handle = sim.handle();
+            let events = events.clone();
+            sim.spawn(async move {
+                for round in 0..4u64 {
+                    handle.sleep((id as u64 % 3 + round) * HOUR).await;
+                    events.borrow_mut().push((handle.now(), id));
+                }
+            });
+        }
+
+        let end = sim.run();
+        let events = events.borrow().clone();
+        (end, events)
+    }
+
+    #[test]
+    fn virtual_time_test() {
+        let (end, events) = trace(1);
+
+        assert_eq!(events.len(), 32);
+        assert_eq!(end, 14 * HOUR);
+        assert!(events.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+
+    #[test]
+    fn deterministic_test() {
+        assert_eq!(trace(42), trace(42));
+
+        // tasks that wake on the same tick are ordered by the seed
+        let orders: Vec<_> = (0..8).map(|seed| trace(seed).1).collect();
+        assert!(orders.iter().any(|order| *order != orders[0]));
+    }
+
+    #[test]
+    fn spawn_and_cancel_test() {
+        let mut sim = Sim::new(0);
+        let handle = sim.handle();
+        let fired = Rc::new(RefCell::new(vec![]));
+
+        let inner = fired.clone();
+        sim.spawn(async move {
+            let child = handle.clone();
+            let fired = inner.clone();
+            handle.spawn(async move {
+                child.sleep(10).await;
+                fired.borrow_mut().push(child.now());
+            });
+
+            // dropping a registered sleep removes it from the wheel
+            let mut timeout = handle.sleep(1_000_000);
+            assert!(futures::poll!(&mut timeout).is_pending());
+            drop(timeout);
+
+            handle.sleep(20).await;
+            inner.borrow_mut().push(handle.now());
+        });
+
+        assert_eq!(sim.run(), 20);
+        assert_eq!(*fired.borrow(), [10, 20]);
+    }
+}
@@ -0,0 +1,26 @@
+use alloc::vec::Vec;
+
+/// A portable copy of the entries scheduled in a [`Wheel`](crate::Wheel)
+///
+/// Created by [`Wheel::snapshot`](crate::Wheel::snapshot) and consumed by
+/// [`Wheel::restore`](crate::Wheel::restore).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot<T, P> {
+    /// The tick of the wheel when the snapshot was taken
+    pub ticks: T,
+    /// The number of leading records that had already expired and were pending a wake
+    pub pending: usize,
+    /// The scheduled entries in nondecreasing deadline order
+    pub records: Vec<Record<T, P>>,
+}
+
+/// A single entry in a [`Snapshot`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record<T, P> {
+    /// The absolute tick at which the entry expires
+    pub deadline: T,
+    /// The caller-provided representation of the entry
+    pub payload: P,
+}
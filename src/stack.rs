@@ -1,6 +1,6 @@
 use super::{
     bitset::Bitset,
-    entry::{Entry, Queue},
+    entry::{ConstQueue, Entry, Queue},
 };
 use arr_macro::arr;
 use core::{fmt, marker::PhantomData};
@@ -9,6 +9,7 @@ pub struct Stack<E: Entry> {
     slots: [E::Queue; 256],
     pub(crate) occupied: Bitset,
     current: u8,
+    len: usize,
     entry: PhantomData<E>,
 }
 
@@ -25,6 +26,7 @@ impl<E: Entry> fmt::Debug for Stack<E> {
         let mut s = f.debug_struct("Stack");
 
         s.field("current", &self.current);
+        s.field("len", &self.len);
 
         if alternate {
             s.field("occupied", &DebugQueues(self));
@@ -41,16 +43,27 @@ struct DebugQueues<'a, E: Entry>(&'a Stack<E>);
 impl<'a, E: Entry> fmt::Debug for DebugQueues<'a, E> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut s = f.debug_map();
-        for i in 0..=255 {
-            if self.0.occupied.get(i) {
-                s.entry(&i, &self.0.slots[i as usize].count());
-            }
+        for i in self.0.occupied.iter() {
+            s.entry(&i, &self.0.slots[i as usize].count());
         }
 
         s.finish()
     }
 }
 
+impl<E: Entry> Stack<E>
+where
+    E::Queue: ConstQueue<E>,
+{
+    pub const NEW: Self = Self {
+        slots: [const { E::Queue::NEW }; 256],
+        occupied: Bitset::EMPTY,
+        current: 0,
+        len: 0,
+        entry: PhantomData,
+    };
+}
+
 impl<E: Entry> Stack<E> {
     pub fn new() -> Self {
         let slots = arr![E::Queue::new(); 256];
@@ -58,6 +71,7 @@ impl<E: Entry> Stack<E> {
             slots,
             occupied: Default::default(),
             current: 0,
+            len: 0,
             entry: PhantomData,
         }
     }
@@ -66,16 +80,43 @@ impl<E: Entry> Stack<E> {
         self.current
     }
 
+    /// Moves the cursor without taking any slots
+    pub fn set_current(&mut self, current: u8) {
+        self.current = current;
+    }
+
     pub fn is_empty(&self) -> bool {
         self.occupied.is_empty()
     }
 
+    /// Returns the number of entries in the stack
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     pub fn insert(&mut self, index: u8, entry: E) {
         self.occupied.insert(index);
+        self.len += 1;
         let list = self.slot_mut(index);
         list.push(entry);
     }
 
+    pub fn remove(&mut self, index: u8, entry: &E) -> Option<E> {
+        if !self.occupied.get(index) {
+            return None;
+        }
+
+        let list = self.slot_mut(index);
+        let entry = list.remove(entry)?;
+
+        if list.is_empty() {
+            self.occupied.remove(index);
+        }
+        self.len -= 1;
+
+        Some(entry)
+    }
+
     fn next_occupied(&self, current: u8) -> (u8, bool) {
         if let Some(next) = self.occupied.next_occupied(current) {
             (next, false)
@@ -84,16 +125,20 @@ impl<E: Entry> Stack<E> {
         }
     }
 
-    pub fn tick(&mut self, can_skip: bool) -> (E::Queue, bool) {
+    /// Moves the cursor to the next slot that needs to be visited
+    ///
+    /// The entries in the new slot are popped with [`Stack::pop_front`].
+    pub fn tick(&mut self, can_skip: bool) -> bool {
         let (current, wrapped) = self.next_tick(can_skip);
         self.current = current;
-        let slot = self.take();
-        (slot, wrapped)
+        wrapped
     }
 
     pub fn next_tick(&self, can_skip: bool) -> (u8, bool) {
         let (mut current, mut wrapped) = self.current.overflowing_add(1);
-        if can_skip {
+        // slots behind the cursor may hold entries for the next rotation so
+        // only look ahead if the cursor hasn't wrapped
+        if can_skip && !wrapped {
             let (next, did_wrap) = self.next_occupied(current);
             current = next;
             wrapped = did_wrap;
@@ -101,10 +146,86 @@ impl<E: Entry> Stack<E> {
         (current, wrapped)
     }
 
-    pub fn take(&mut self) -> E::Queue {
-        let current = self.current;
-        self.occupied.remove(current);
-        self.slot_mut(current).take()
+    /// Returns the first occupied slot between `first` and `last`
+    pub fn next_slot(&self, first: u8, last: u8) -> Option<(u8, &E::Queue)> {
+        if !self.occupied.any(first, last) {
+            return None;
+        }
+        let index = self.occupied.next_occupied(first)?;
+        Some((index, &self.slots[index as usize]))
+    }
+
+    /// Returns the index of the first occupied slot between `first` and `last`
+    pub fn first_occupied(&self, first: u8, last: u8) -> Option<u8> {
+        self.next_slot(first, last).map(|(index, _)| index)
+    }
+
+    /// Moves the entries that match the predicate into the same slots of `other`
+    ///
+    /// Returns the number of entries that were moved
+    pub fn split_into<F: FnMut(&E) -> bool>(&mut self, other: &mut Self, f: &mut F) -> usize {
+        let mut moved = 0;
+
+        // entries are put back into the slots they came from so the set of
+        // occupied slots can only shrink
+        let occupied = self.occupied;
+        for next in occupied.iter() {
+            self.occupied.remove(next);
+            let mut slot = self.slot_mut(next).take();
+            while let Some(entry) = slot.pop() {
+                self.len -= 1;
+                if f(&entry) {
+                    moved += 1;
+                    other.insert(next, entry);
+                } else {
+                    self.insert(next, entry);
+                }
+            }
+        }
+
+        moved
+    }
+
+    /// Returns the first occupied slot after the cursor
+    ///
+    /// Slots at or behind the cursor are only searched if the ones ahead of it
+    /// are empty, which is indicated by returning `true`.
+    pub fn first_slot(&self) -> Option<(&E::Queue, bool)> {
+        let index = self
+            .occupied
+            .next_occupied_wrapping(self.current.wrapping_add(1))?;
+        let wrapped = index <= self.current;
+
+        Some((&self.slots[index as usize], wrapped))
+    }
+
+    /// Pops the entry at the front of the slot if it matches the predicate
+    pub fn pop_front_if<F: FnOnce(&E) -> bool>(&mut self, index: u8, f: F) -> Option<E> {
+        if !self.occupied.get(index) {
+            return None;
+        }
+
+        if !self.slots[index as usize].with_front(f)? {
+            return None;
+        }
+
+        self.pop_front(index)
+    }
+
+    /// Pops the entry at the front of the slot
+    pub fn pop_front(&mut self, index: u8) -> Option<E> {
+        if !self.occupied.get(index) {
+            return None;
+        }
+
+        let list = self.slot_mut(index);
+        let entry = list.pop()?;
+        if list.is_empty() {
+            self.occupied.remove(index);
+        }
+        self.len -= 1;
+
+        Some(entry)
     }
 
     fn slot_mut(&mut self, index: u8) -> &mut E::Queue {
@@ -114,3 +235,25 @@ impl<E: Entry> Stack<E> {
         unsafe { self.slots.get_unchecked_mut(index as usize) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::atomic;
+
+    #[test]
+    fn next_tick_test() {
+        let mut stack = Stack::<atomic::ArcEntry>::default();
+        stack.insert(3, atomic::Entry::new(3));
+
+        stack.set_current(0);
+        assert_eq!(stack.next_tick(true), (3, false));
+        assert_eq!(stack.next_tick(false), (1, false));
+
+        // the occupied slot is behind the cursor so it isn't reached until
+        // the next rotation
+        stack.set_current(255);
+        assert_eq!(stack.next_tick(true), (0, true));
+        assert_eq!(stack.next_tick(false), (0, true));
+    }
+}
@@ -0,0 +1,393 @@
+//! Differential testing utilities
+//!
+//! [`Harness`] applies a sequence of [`Op`]s to both a [`Wheel`] and a simple
+//! reference model backed by a `BTreeMap`, asserting that the two agree after
+//! every operation. Crates that provide their own [`Entry`], [`Queue`] or
+//! [`Storage`] implementations can drive it from their fuzzer of choice to
+//! check the implementations against the expected wheel behavior.
+//!
+//! [`Queue`]: crate::entry::Queue
+
+use crate::{
+    entry::{Entry, Storage, Tick},
+    Wheel,
+};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+#[cfg(any(test, feature = "bolero"))]
+use bolero_generator::TypeGenerator;
+
+/// An operation applied by the [`Harness`]
+///
+/// With the `bolero` feature enabled, operations can be generated by fuzzers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "bolero"), derive(TypeGenerator))]
+pub enum Op<T> {
+    /// Creates an entry with the given delay and inserts it
+    Insert { delay: T },
+    /// Creates an entry with the given delay and inserts it with an absolute deadline
+    InsertDeadline { delay: T, deadline: T },
+    /// Cancels a previously inserted entry, selected by index modulo the
+    /// number of inserted entries
+    Cancel { index: usize },
+    /// Restarts a previously inserted entry, selected by index modulo the
+    /// number of inserted entries
+    Reschedule { index: usize },
+    /// Advances the wheel by the given number of ticks with [`Wheel::advance_to`]
+    AdvanceTo { delta: T },
+    /// Calls [`Wheel::skip`]
+    Skip,
+    /// Calls [`Wheel::wake`]
+    Wake,
+    /// Calls [`Wheel::next_expiration`]
+    NextExpiration,
+    /// Calls [`Wheel::set_cascade_budget`]
+    CascadeBudget { budget: u8 },
+}
+
+/// Checks a [`Wheel`] against a reference model
+pub struct Harness<E: Entry, F> {
+    wheel: Wheel<E>,
+    new_entry: F,
+    entries: Vec<E>,
+    /// The deadline of each scheduled entry, keyed by its index in `entries`
+    model: BTreeMap<usize, <E::Storage as Storage<E>>::Tick>,
+    now: <E::Storage as Storage<E>>::Tick,
+}
+
+/// Applies each operation to a new [`Harness`]
+///
+/// `new_entry` is called to create an entry with the given delay.
+pub fn check<E, F>(ops: &[Op<<E::Storage as Storage<E>>::Tick>], new_entry: F)
+where
+    E: Entry + Clone,
+    F: FnMut(<E::Storage as Storage<E>>::Tick) -> E,
+    <E::Storage as Storage<E>>::Tick: fmt::Debug,
+{
+    let mut harness = Harness::new(new_entry);
+    for op in ops.iter().copied() {
+        harness.apply(op);
+    }
+    harness.finish();
+}
+
+impl<E, F> Harness<E, F>
+where
+    E: Entry + Clone,
+    F: FnMut(<E::Storage as Storage<E>>::Tick) -> E,
+    <E::Storage as Storage<E>>::Tick: fmt::Debug,
+{
+    pub fn new(new_entry: F) -> Self {
+        Self {
+            wheel: Wheel::default(),
+            new_entry,
+            entries: Vec::new(),
+            model: BTreeMap::new(),
+            now: Default::default(),
+        }
+    }
+
+    /// Returns the wheel being tested
+    pub fn wheel(&self) -> &Wheel<E> {
+        &self.wheel
+    }
+
+    /// Applies the operation to both the wheel and the model
+    ///
+    /// Operations that would move the model past the end of the tick range
+    /// are ignored.
+    pub fn apply(&mut self, op: Op<<E::Storage as Storage<E>>::Tick>) {
+        match op {
+            Op::Insert { delay } => {
+                if let Some(deadline) = self.checked_deadline(delay) {
+                    let entry = (self.new_entry)(delay);
+                    self.model.insert(self.entries.len(), deadline);
+                    self.entries.push(entry.clone());
+                    self.wheel.insert(entry);
+                }
+            }
+            Op::InsertDeadline { delay, deadline } => {
+                let entry = (self.new_entry)(delay);
+                self.model.insert(self.entries.len(), deadline);
+                self.entries.push(entry.clone());
+                self.wheel.insert_deadline(entry, deadline);
+            }
+            Op::Cancel { index } => {
+                if let Some(index) = self.index(index) {
+                    let expected = self.model.remove(&index).is_some();
+                    let actual = self.wheel.cancel(&self.entries[index]).is_some();
+                    assert_eq!(expected, actual, "cancel({})", index);
+                }
+            }
+            Op::Reschedule { index } => {
+                if let Some(index) = self.index(index) {
+                    if !self.model.contains_key(&index) {
+                        assert!(!self.wheel.reschedule(&self.entries[index]));
+                    } else if let Some(deadline) =
+                        self.checked_deadline(self.entries[index].delay())
+                    {
+                        self.model.insert(index, deadline);
+                        assert!(self.wheel.reschedule(&self.entries[index]));
+                    }
+                }
+            }
+            Op::AdvanceTo { delta } => {
+                if let Some(target) = self.checked_deadline(delta) {
+                    assert_eq!(self.wheel.advance_to(target), delta);
+                    self.now = target;
+                }
+            }
+            Op::Skip => {
+                let expected = self.next_deadline().map(|deadline| {
+                    if deadline > self.now {
+                        let delta = deadline - self.now;
+                        self.now = deadline;
+                        delta
+                    } else {
+                        Default::default()
+                    }
+                });
+                assert_eq!(self.wheel.skip(), expected);
+            }
+            Op::Wake => {
+                let now = self.now;
+                let mut expected = Vec::new();
+                self.model.retain(|_, deadline| {
+                    if *deadline <= now {
+                        expected.push(*deadline);
+                        false
+                    } else {
+                        true
+                    }
+                });
+                expected.sort_unstable();
+
+                let mut actual = Vec::new();
+                self.wheel.wake(|entry| actual.push(entry.deadline()));
+                actual.sort_unstable();
+
+                assert_eq!(expected, actual);
+            }
+            Op::NextExpiration => {
+                let now = self.now;
+                let scheduled = self.model.values().copied().filter(|d| *d > now).min();
+
+                match (self.wheel.next_expiration(), scheduled) {
+                    (None, None) => {}
+                    (Some(next), Some(deadline)) => {
+                        // the next expiration is a lower bound as entries in
+                        // higher stacks still need to be cascaded
+                        assert!(
+                            now < next && next <= deadline,
+                            "{:?}",
+                            (now, next, deadline)
+                        );
+                    }
+                    (actual, expected) => {
+                        panic!("next_expiration() = {:?}, expected {:?}", actual, expected)
+                    }
+                }
+            }
+            Op::CascadeBudget { budget } => {
+                self.wheel.set_cascade_budget(budget as usize);
+            }
+        }
+
+        assert_eq!(self.wheel.ticks(), self.now, "{:?}", op);
+        assert_eq!(self.wheel.len(), self.model.len(), "{:?}", op);
+    }
+
+    /// Drains the wheel and checks that everything expires at the expected tick
+    pub fn finish(mut self) {
+        loop {
+            self.apply(Op::Wake);
+            if self.model.is_empty() {
+                break;
+            }
+            self.apply(Op::Skip);
+        }
+
+        assert!(self.wheel.is_empty());
+        assert_eq!(self.wheel.skip(), None);
+    }
+
+    fn checked_deadline(
+        &self,
+        delay: <E::Storage as Storage<E>>::Tick,
+    ) -> Option<<E::Storage as Storage<E>>::Tick> {
+        let deadline = self.now.wrapping_add(delay);
+        if deadline < self.now {
+            None
+        } else {
+            Some(deadline)
+        }
+    }
+
+    fn index(&self, index: usize) -> Option<usize> {
+        if self.entries.is_empty() {
+            None
+        } else {
+            Some(index % self.entries.len())
+        }
+    }
+
+    fn next_deadline(&self) -> Option<<E::Storage as Storage<E>>::Tick> {
+        self.model.values().copied().min()
+    }
+}
+
+impl<T: Tick> Op<T> {
+    /// Maps the ticks in the operation
+    pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Op<U> {
+        match self {
+            Op::Insert { delay } => Op::Insert { delay: f(delay) },
+            Op::InsertDeadline { delay, deadline } => {
+                let delay = f(delay);
+                Op::InsertDeadline {
+                    delay,
+                    deadline: f(deadline),
+                }
+            }
+            Op::Cancel { index } => Op::Cancel { index },
+            Op::Reschedule { index } => Op::Reschedule { index },
+            Op::AdvanceTo { delta } => Op::AdvanceTo { delta: f(delta) },
+            Op::Skip => Op::Skip,
+            Op::Wake => Op::Wake,
+            Op::NextExpiration => Op::NextExpiration,
+            Op::CascadeBudget { budget } => Op::CascadeBudget { budget },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::atomic;
+    use alloc::vec;
+    use bolero::{check, RngEngine, TestTarget};
+
+    #[test]
+    fn differential_test() {
+        check!().with_type::<Vec<Op<u64>>>().for_each(|ops| {
+            // keep the deltas small enough to exercise all of the stacks
+            // without running out of tick range
+            let ops: Vec<_> = ops
+                .iter()
+                .map(|op| op.map(|ticks| ticks >> (ticks % 64)))
+                .collect();
+            super::check(&ops, atomic::Entry::new);
+        });
+    }
+
+    #[test]
+    fn fixed_differential_test() {
+        use crate::entry::fixed;
+        use alloc::boxed::Box;
+
+        check!().with_type::<Vec<Op<u32>>>().for_each(|ops| {
+            let ops: Vec<_> = ops
+                .iter()
+                .map(|op| op.map(|ticks| ticks >> (ticks % 32)))
+                .collect();
+            super::check(&ops, |delay| {
+                let entry: fixed::StaticEntry = Box::leak(Box::new(fixed::Entry::new(delay)));
+                entry
+            });
+        });
+    }
+
+    #[test]
+    fn advance_test() {
+        super::check(
+            &[
+                Op::Insert { delay: 5 },
+                Op::Insert { delay: 300 },
+                Op::Insert { delay: 70_000 },
+                Op::AdvanceTo { delta: 4 },
+                Op::NextExpiration,
+                Op::AdvanceTo { delta: 1 },
+                Op::Wake,
+                Op::AdvanceTo { delta: 295 },
+                Op::Wake,
+                Op::Insert { delay: 300 },
+                Op::AdvanceTo { delta: 1000 },
+                Op::Wake,
+                Op::Reschedule { index: 2 },
+                Op::Cancel { index: 0 },
+                Op::AdvanceTo { delta: 1 << 40 },
+                Op::Wake,
+            ],
+            atomic::Entry::new,
+        );
+    }
+
+    #[test]
+    fn cascade_budget_test() {
+        let mut ops = vec![Op::CascadeBudget { budget: 2 }];
+        for delay in [600, 520, 700, 513, 600, 1000, 70_000, 70_300].iter() {
+            ops.push(Op::Insert { delay: *delay });
+        }
+        for _ in 0..300 {
+            ops.push(Op::AdvanceTo { delta: 3 });
+            ops.push(Op::NextExpiration);
+            ops.push(Op::Wake);
+        }
+
+        super::check(&ops, atomic::Entry::new);
+    }
+
+    #[test]
+    fn cascade_budget_fuzz_test() {
+        TestTarget::new(RngEngine::default().with_iterations(10_000))
+            .with_type::<(u8, Vec<Op<u64>>)>()
+            .for_each(|(budget, ops)| {
+                let budget = Op::CascadeBudget {
+                    budget: budget % 4 + 1,
+                };
+                let ops: Vec<_> = core::iter::once(budget)
+                    .chain(ops.iter().map(|op| op.map(|ticks| ticks >> (ticks % 64))))
+                    .collect();
+                super::check(&ops, atomic::Entry::new);
+            });
+    }
+
+    #[test]
+    fn cascade_skip_test() {
+        // entries cascaded ahead of time into slot 0 of the lowest stack
+        // used to let the stacks above skip past the slot they came from
+        super::check(
+            &[
+                Op::CascadeBudget { budget: 1 },
+                Op::AdvanceTo { delta: 1_138_915 },
+                Op::Insert { delay: 29 },
+                Op::Insert { delay: 1_511_881 },
+                Op::Insert { delay: 2 },
+                Op::AdvanceTo { delta: 59_598 },
+                Op::Wake,
+            ],
+            atomic::Entry::new,
+        );
+    }
+
+    #[test]
+    fn cascade_budget_fixed_test() {
+        use crate::entry::fixed;
+        use alloc::boxed::Box;
+
+        let mut ops = vec![Op::CascadeBudget { budget: 1 }];
+        for delay in [70_000, 65_800, 66_000, 65_600, 70_000].iter() {
+            ops.push(Op::Insert { delay: *delay });
+        }
+        for _ in 0..400 {
+            ops.push(Op::AdvanceTo { delta: 250 });
+            ops.push(Op::Skip);
+            ops.push(Op::Wake);
+        }
+
+        super::check(&ops, |delay| {
+            let entry: fixed::StaticEntry = Box::leak(Box::new(fixed::Entry::new(delay)));
+            entry
+        });
+    }
+}
@@ -1,28 +1,70 @@
-use crate::entry::{Entry, Queue, Storage, Tick};
-use core::fmt;
+use crate::{
+    entry::{ConstQueue, ConstStorage, Entry, EntryRef, Queue, Storage, Tick},
+    error::Error,
+    observer::WheelObserver,
+};
+use core::{convert::TryFrom, fmt};
 
-pub struct Wheel<E: Entry> {
+#[cfg(feature = "alloc")]
+use crate::snapshot::{Record, Snapshot};
+
+type Woken<E> = (E, WakeInfo<<<E as Entry>::Storage as Storage<E>>::Tick>);
+
+pub struct Wheel<E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick> = ()> {
     stacks: E::Storage,
     pending_wake: E::Queue,
+    /// Entries that expire after the tick range wraps around
+    overflow: E::Queue,
+    overflow_len: usize,
+    len: usize,
+    cascades: u64,
+    max_cascade: usize,
+    cascade_budget: usize,
+    woken: u64,
+    observer: O,
 }
 
 impl<E: Entry> Default for Wheel<E> {
     fn default() -> Self {
+        Self::with_observer(())
+    }
+}
+
+impl<E: Entry> Wheel<E>
+where
+    E::Queue: ConstQueue<E>,
+    E::Storage: ConstStorage<E>,
+{
+    /// Creates an empty wheel in a const context
+    ///
+    /// This allows the wheel to be placed in a `static` when paired with an
+    /// entry type like [`fixed::Entry`](crate::entry::fixed::Entry).
+    pub const fn new() -> Self {
         Self {
-            stacks: Default::default(),
-            pending_wake: E::Queue::new(),
+            stacks: E::Storage::NEW,
+            pending_wake: E::Queue::NEW,
+            overflow: E::Queue::NEW,
+            overflow_len: 0,
+            len: 0,
+            cascades: 0,
+            max_cascade: 0,
+            cascade_budget: 0,
+            woken: 0,
+            observer: (),
         }
     }
 }
 
-impl<E: Entry> fmt::Debug for Wheel<E>
+impl<E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick>> fmt::Debug for Wheel<E, O>
 where
     <E::Storage as Storage<E>>::Tick: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Wheel")
             .field("ticks", &self.ticks())
-            .field("pending_wake", &self.pending_wake.count())
+            .field("len", &self.len)
+            .field("pending_wake", &self.pending_len())
+            .field("overflow", &self.overflow_len)
             .field("stacks", &<StacksDebug<E>>::new(&self.stacks))
             .finish()
     }
@@ -46,19 +88,264 @@ impl<'a, E: Entry> fmt::Debug for StacksDebug<'a, E> {
     }
 }
 
-impl<E: Entry> Wheel<E> {
+impl<E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick>> Wheel<E, O> {
+    /// Creates an empty wheel that reports its activity to `observer`
+    pub fn with_observer(observer: O) -> Self {
+        Self {
+            stacks: Default::default(),
+            pending_wake: E::Queue::new(),
+            overflow: E::Queue::new(),
+            overflow_len: 0,
+            len: 0,
+            cascades: 0,
+            max_cascade: 0,
+            cascade_budget: 0,
+            woken: 0,
+            observer,
+        }
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
     pub fn ticks(&self) -> <E::Storage as Storage<E>>::Tick {
         self.stacks.ticks()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.stacks.is_empty()
+        self.stacks.is_empty() && self.overflow.is_empty()
+    }
+
+    /// Returns the number of entries in the wheel, including the ones pending a wake
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns a snapshot of the wheel's occupancy and activity counters
+    ///
+    /// The counters for each stack are returned by [`Wheel::stack_stats`].
+    pub fn stats(&self) -> Stats {
+        Stats {
+            len: self.len,
+            pending: self.pending_len(),
+            overflow: self.overflow_len,
+            cascades: self.cascades,
+            max_cascade: self.max_cascade,
+            woken: self.woken,
+        }
+    }
+
+    /// Returns the occupancy counters for each stack, starting with the finest resolution
+    pub fn stack_stats(&self) -> impl ExactSizeIterator<Item = StackStats> + '_ {
+        self.stacks.as_ref().iter().map(|stack| StackStats {
+            entries: stack.len(),
+            occupied_slots: stack.occupied.len(),
+        })
     }
 
+    pub(crate) fn pending_len(&self) -> usize {
+        let scheduled: usize = self.stacks.as_ref().iter().map(|s| s.len()).sum();
+        self.len - scheduled - self.overflow_len
+    }
+
+    /// Inserts the entry to expire `delay` ticks from now
+    ///
+    /// A deadline past the end of the tick range is held in an overflow list
+    /// until the wheel wraps around. Use [`Wheel::try_insert`] to reject
+    /// those entries instead.
+    ///
+    /// Inserting an entry that is already scheduled in this wheel restarts its
+    /// timer, the same as [`Wheel::reschedule`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry is scheduled in another wheel
     pub fn insert(&mut self, mut entry: E) {
+        self.unlink(&entry);
         let ticks = self.ticks();
         entry.set_start_tick(ticks);
-        self.insert_at(entry, ticks, ticks);
+        self.schedule(entry);
+    }
+
+    /// Inserts the entry to expire `delay` ticks from now
+    ///
+    /// Returns [`Error::AlreadyScheduled`] if the entry is already held in a
+    /// wheel, or [`Error::DeadlineOutOfRange`] if the deadline lies past the
+    /// end of the tick range rather than wrapping around.
+    pub fn try_insert(&mut self, entry: E) -> Result<(), Error> {
+        if entry.is_scheduled() {
+            return Err(Error::AlreadyScheduled);
+        }
+
+        let ticks = self.ticks();
+        if ticks.wrapping_add(entry.delay()) < ticks {
+            return Err(Error::DeadlineOutOfRange);
+        }
+
+        self.insert(entry);
+        Ok(())
+    }
+
+    /// Inserts the entry to expire at an absolute tick
+    ///
+    /// The entry's start tick is set so that `start_tick + delay` lands on the
+    /// deadline. A deadline at or before the current tick is moved straight to
+    /// the pending queue rather than wrapping around the tick range, so it will
+    /// be woken by the next call to [`Wheel::wake`].
+    ///
+    /// Inserting an entry that is already scheduled in this wheel moves it to
+    /// the new deadline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry is scheduled in another wheel
+    pub fn insert_deadline(&mut self, mut entry: E, deadline: <E::Storage as Storage<E>>::Tick) {
+        self.unlink(&entry);
+        let now = self.ticks();
+        entry.set_start_tick(deadline.wrapping_sub(entry.delay()));
+
+        if deadline > now {
+            self.schedule(entry);
+            return;
+        }
+
+        self.len += 1;
+        entry.on_schedule();
+        self.push_pending(entry);
+        self.observer.on_insert(None, now, deadline);
+    }
+
+    /// Inserts the entry to expire at an absolute tick
+    ///
+    /// Returns [`Error::AlreadyScheduled`] if the entry is already held in a
+    /// wheel, or [`Error::DeadlineInPast`] if the deadline is before the
+    /// current tick. A deadline equal to the current tick is pending
+    /// immediately.
+    pub fn try_insert_deadline(
+        &mut self,
+        entry: E,
+        deadline: <E::Storage as Storage<E>>::Tick,
+    ) -> Result<(), Error> {
+        if entry.is_scheduled() {
+            return Err(Error::AlreadyScheduled);
+        }
+
+        if deadline < self.ticks() {
+            return Err(Error::DeadlineInPast);
+        }
+
+        self.insert_deadline(entry, deadline);
+        Ok(())
+    }
+
+    /// Removes an entry that is being inserted again so it isn't linked twice
+    fn unlink(&mut self, entry: &E) {
+        if entry.is_scheduled() {
+            assert!(
+                self.remove(entry).is_some(),
+                "the entry is already scheduled in another wheel"
+            );
+        }
+    }
+
+    /// Places an entry that has its start tick already set
+    fn schedule(&mut self, entry: E) {
+        let now = self.ticks();
+        let start_tick = entry.start_tick();
+        let deadline = entry.deadline();
+        self.len += 1;
+        entry.on_schedule();
+
+        // the deadline wrapped around the tick range so the entry can't be
+        // placed until the top stack wraps
+        if deadline < now {
+            self.overflow.push(entry);
+            self.overflow_len += 1;
+            self.observer
+                .on_insert(Some(self.stacks.len()), now, deadline);
+            return;
+        }
+
+        let stack = self.insert_at(entry, now, start_tick);
+        self.observer.on_insert(stack, now, deadline);
+    }
+
+    fn push_pending(&mut self, entry: E) {
+        entry.on_expire();
+        self.pending_wake.push_sorted(entry);
+    }
+
+    /// Removes the entry from the wheel
+    ///
+    /// Returns `None` if the entry was not scheduled in this wheel
+    pub fn cancel(&mut self, entry: &E) -> Option<E> {
+        let entry = self.remove(entry)?;
+        entry.on_cancel();
+        Some(entry)
+    }
+
+    /// Removes the entry from the wheel without cancelling it, so it can be
+    /// scheduled again
+    fn remove(&mut self, entry: &E) -> Option<E> {
+        let deadline = entry.deadline();
+
+        let (entry, stack) = if let Some(entry) = self.pending_wake.remove(entry) {
+            (entry, None)
+        } else if let Some(entry) = self.overflow.remove(entry) {
+            self.overflow_len -= 1;
+            (entry, Some(self.stacks.len()))
+        } else {
+            // the entry can only be in the slot matching its deadline in each stack
+            let positions = deadline.to_le_bytes();
+            positions
+                .as_ref()
+                .iter()
+                .enumerate()
+                .find_map(|(index, position)| {
+                    let entry = self.stacks.get_mut(index).remove(*position, entry)?;
+                    Some((entry, Some(index)))
+                })?
+        };
+
+        self.len -= 1;
+        self.observer.on_cancel(stack, self.ticks(), deadline);
+
+        Some(entry)
+    }
+
+    /// Restarts the entry's timer from the current tick
+    ///
+    /// Returns `false` if the entry was not scheduled in this wheel
+    pub fn reschedule(&mut self, entry: &E) -> bool {
+        if let Some(entry) = self.remove(entry) {
+            self.insert(entry);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Restarts the entry's timer from the current tick
+    ///
+    /// Returns [`Error::NotScheduled`] if the entry was not scheduled in this
+    /// wheel, or [`Error::DeadlineOutOfRange`] if the new deadline lies past
+    /// the end of the tick range. The entry is left untouched on error.
+    pub fn try_reschedule(&mut self, entry: &E) -> Result<(), Error> {
+        let ticks = self.ticks();
+        if ticks.wrapping_add(entry.delay()) < ticks {
+            return Err(Error::DeadlineOutOfRange);
+        }
+
+        if self.reschedule(entry) {
+            Ok(())
+        } else {
+            Err(Error::NotScheduled)
+        }
     }
 
     fn insert_at(
@@ -66,27 +353,33 @@ impl<E: Entry> Wheel<E> {
         entry: E,
         now: <E::Storage as Storage<E>>::Tick,
         start_tick: <E::Storage as Storage<E>>::Tick,
-    ) -> bool {
+    ) -> Option<usize> {
         let delay = entry.delay();
         let absolute_time = delay.wrapping_add(start_tick);
-        let zero_time = (absolute_time ^ now).to_be();
+        let zero_time = absolute_time ^ now;
 
         // The entry should be woken up
         if zero_time.is_zero() {
-            self.pending_wake.push(entry);
-            return true;
+            self.push_pending(entry);
+            return None;
         }
 
-        // find the stack in which the entry belongs
+        // find the stack in which the entry belongs; this is the most
+        // significant byte that differs from the current time.
+        //
+        // The placement only depends on the deadline and the current time so
+        // entries with equal deadlines always share a slot. Since the slots
+        // are FIFO and taken as a whole, this keeps them in insertion order.
         let absolute_bytes = absolute_time.to_le_bytes();
+        let absolute_bytes = absolute_bytes.as_ref();
         let leading = zero_time.leading_zeros();
 
-        let index = (leading / 8) as usize;
-        let position = absolute_bytes.as_ref()[index];
+        let index = absolute_bytes.len() - 1 - (leading / 8) as usize;
+        let position = absolute_bytes[index];
 
         self.stacks.get_mut(index).insert(position, entry);
 
-        false
+        Some(index)
     }
 
     pub fn next_expiration(&self) -> Option<<E::Storage as Storage<E>>::Tick> {
@@ -125,12 +418,208 @@ impl<E: Entry> Wheel<E> {
         Some(next.elapsed_since(now))
     }
 
-    pub fn set_current_tick(&mut self, _ticks: <E::Storage as Storage<E>>::Tick) -> Option<bool> {
-        if self.is_empty() {
-            return None;
+    /// Advances the wheel to the given tick
+    ///
+    /// Any entries that expire along the way are moved to the pending queue
+    /// and can be woken with [`Wheel::wake`]. The wheel only moves forward so
+    /// a target behind the current tick wraps around the tick range.
+    ///
+    /// Returns the number of ticks that the wheel advanced
+    pub fn advance_to(
+        &mut self,
+        target: <E::Storage as Storage<E>>::Tick,
+    ) -> <E::Storage as Storage<E>>::Tick {
+        let start = self.ticks();
+
+        loop {
+            let now = self.ticks();
+            let remaining = target.elapsed_since(now);
+
+            if remaining.is_zero() {
+                break;
+            }
+
+            match self.next_expiration() {
+                Some(next) if next.elapsed_since(now) <= remaining => {
+                    self.skip_once();
+                }
+                _ => {
+                    // there are no occupied slots between now and the target
+                    // so the cursors can be moved directly
+                    let bytes = target.to_le_bytes();
+                    for (index, current) in bytes.as_ref().iter().enumerate() {
+                        self.stacks.get_mut(index).set_current(*current);
+                    }
+                    self.observer.on_advance(now, target);
+
+                    let cascades = self.cascades;
+                    self.pre_cascade();
+                    self.record_cascades(cascades);
+                    break;
+                }
+            }
+        }
+
+        target.elapsed_since(start)
+    }
+
+    /// Advances the wheel to the given tick
+    ///
+    /// Returns [`Error::TimeWentBackwards`] instead of wrapping around the
+    /// tick range if the target is before the current tick.
+    pub fn try_advance_to(
+        &mut self,
+        target: <E::Storage as Storage<E>>::Tick,
+    ) -> Result<<E::Storage as Storage<E>>::Tick, Error> {
+        if target < self.ticks() {
+            return Err(Error::TimeWentBackwards);
+        }
+
+        Ok(self.advance_to(target))
+    }
+
+    /// Returns the exact number of ticks until the earliest entry expires,
+    /// capped at `max_sleep`
+    ///
+    /// Unlike [`Wheel::next_delta`], which stops at the next populated slot
+    /// even if the entries there still need to cascade, this looks at the
+    /// entries themselves so a tickless system can sleep until the earliest
+    /// one is due. Returns zero if entries are already pending a wake.
+    pub fn next_alarm(
+        &self,
+        max_sleep: <E::Storage as Storage<E>>::Tick,
+    ) -> <E::Storage as Storage<E>>::Tick {
+        match self.earliest_deadline() {
+            Some(deadline) => deadline.elapsed_since(self.ticks()).min(max_sleep),
+            None => max_sleep,
+        }
+    }
+
+    fn earliest_deadline(&self) -> Option<<E::Storage as Storage<E>>::Tick> {
+        let now = self.ticks();
+
+        if !self.pending_wake.is_empty() {
+            return Some(now);
+        }
+
+        let mut earliest: Option<<E::Storage as Storage<E>>::Tick> = None;
+        let mut search = |queue: &E::Queue| {
+            queue.for_each(|entry| {
+                let deadline = entry.deadline();
+                if earliest
+                    .is_none_or(|earliest| deadline.wrapping_sub(now) < earliest.wrapping_sub(now))
+                {
+                    earliest = Some(deadline);
+                }
+            });
+        };
+
+        // entries in lower stacks and slots always expire before the ones in
+        // higher stacks and slots, so only the first occupied slot needs to be
+        // searched. The exception is a slot behind the cursor, which holds
+        // entries cascaded ahead of time from the next stack's next slot.
+        let mut found = false;
+        for index in 0..self.stacks.len() {
+            if let Some((queue, wrapped)) = self.stacks.get(index).first_slot() {
+                search(queue);
+                found = true;
+                if !wrapped {
+                    break;
+                }
+            }
+        }
+
+        if !found {
+            search(&self.overflow);
+        }
+
+        earliest
+    }
+
+    /// Advances the wheel by `elapsed` ticks in a single pass
+    ///
+    /// Intended to be called after waking up from a tickless sleep. Rather than
+    /// stopping at each populated slot along the way, like [`Wheel::advance_to`],
+    /// the slots that were skipped over are taken all at once: the entries
+    /// that expired are moved to the pending queue and the rest are placed
+    /// relative to the new tick.
+    pub fn catch_up(&mut self, elapsed: <E::Storage as Storage<E>>::Tick) {
+        let now = self.ticks();
+        let target = now.wrapping_add(elapsed);
+
+        if target == now {
+            return;
+        }
+
+        // the overflow list is only examined when the top stack wraps
+        if target < now {
+            self.advance_to(target);
+            return;
+        }
+
+        // the most significant stack that the cursors move in
+        let top = {
+            let bytes = now.to_le_bytes();
+            (bytes.as_ref().len() - 1) - ((now ^ target).leading_zeros() / 8) as usize
+        };
+        let target_bytes = target.to_le_bytes();
+
+        for (index, current) in target_bytes.as_ref().iter().enumerate() {
+            self.stacks.get_mut(index).set_current(*current);
+        }
+
+        // everything below the top stack shares its upper bytes with `now` so
+        // it has all expired, apart from the entries that were cascaded ahead
+        // of time; take them first to keep them in deadline order
+        let cascades = self.cascades;
+
+        for index in 0..top {
+            // entries cascaded ahead of time may be due after the target and
+            // land back in the slot they were taken from, so each slot is
+            // only taken once
+            let mut first = 0;
+            while let Some(slot) = self.stacks.get(index).first_occupied(first, u8::MAX) {
+                self.catch_up_slot(index, slot, target);
+                first = match slot.checked_add(1) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
+        }
+
+        // slots behind the cursor in the top stack hold entries for its next
+        // rotation so they're left in place
+        let first = now.to_le_bytes().as_ref()[top] + 1;
+        let last = target_bytes.as_ref()[top];
+        while let Some(slot) = self.stacks.get(top).first_occupied(first, last) {
+            self.catch_up_slot(top, slot, target);
         }
 
-        todo!()
+        self.record_cascades(cascades);
+        self.observer.on_advance(now, target);
+    }
+
+    fn catch_up_slot(&mut self, from: usize, slot: u8, now: <E::Storage as Storage<E>>::Tick) {
+        // drain the slot first since entries may be placed back into it
+        let mut entries = E::Queue::new();
+        while let Some(entry) = self.stacks.get_mut(from).pop_front(slot) {
+            entries.push(entry);
+        }
+
+        while let Some(entry) = entries.pop() {
+            self.cascades += 1;
+            let deadline = entry.deadline();
+
+            let to = if deadline <= now {
+                self.push_pending(entry);
+                None
+            } else {
+                let start_tick = entry.start_tick();
+                self.insert_at(entry, now, start_tick)
+            };
+
+            self.observer.on_cascade(from, to, now, deadline);
+        }
     }
 
     /// Skips the timer to the next populated slot
@@ -164,18 +653,137 @@ impl<E: Entry> Wheel<E> {
     }
 
     fn skip_once(&mut self) -> Option<bool> {
+        let start = self.ticks();
+        let cascades = self.cascades;
+        let result = self.tick_stacks();
+        let end = self.ticks();
+
+        if start != end {
+            self.observer.on_advance(start, end);
+        }
+
+        if result.is_some() {
+            self.pre_cascade();
+        }
+        self.record_cascades(cascades);
+
+        result
+    }
+
+    /// Sets the number of entries that may be cascaded ahead of time on each
+    /// step of the wheel
+    ///
+    /// By default, all of the entries in a higher stack's slot are re-inserted
+    /// the moment its cursor reaches the slot, which can be a latency spike if
+    /// the slot is large. With a budget, each step also moves up to `budget`
+    /// entries from the front of the next slot in each higher stack into the
+    /// slots of the stack below that have already been passed, spreading the
+    /// work over the preceding rotation. Entries are only moved in order, so an
+    /// entry that isn't ready to move holds back the ones behind it; whatever
+    /// is left is cascaded as usual. A budget of `0` disables this.
+    ///
+    /// The effect can be measured with [`Stats::max_cascade`].
+    pub fn set_cascade_budget(&mut self, budget: usize) {
+        self.cascade_budget = budget;
+    }
+
+    pub fn cascade_budget(&self) -> usize {
+        self.cascade_budget
+    }
+
+    fn pre_cascade(&mut self) {
+        let mut budget = self.cascade_budget;
+        let now = self.ticks();
+
+        for index in 0..self.stacks.len() - 1 {
+            let current = self.stacks.get(index).current();
+            let next = match self.stacks.get(index + 1).current().checked_add(1) {
+                Some(next) => next,
+                None => continue,
+            };
+
+            while budget > 0 {
+                // the entry can move to a slot that was already passed in this
+                // rotation, which is taken again once the stack wraps
+                let position = |entry: &E| entry.deadline().to_le_bytes().as_ref()[index];
+                let entry = self
+                    .stacks
+                    .get_mut(index + 1)
+                    .pop_front_if(next, |entry| position(entry) <= current);
+
+                let entry = match entry {
+                    Some(entry) => entry,
+                    None => break,
+                };
+
+                budget -= 1;
+                self.cascades += 1;
+                let deadline = entry.deadline();
+                self.stacks.get_mut(index).insert(position(&entry), entry);
+                self.observer
+                    .on_cascade(index + 1, Some(index), now, deadline);
+            }
+        }
+    }
+
+    /// Returns the tick that the wheel moves to once the stack at `index` wraps
+    ///
+    /// Slot 0 of the stack is taken as soon as it wraps, while the stack above
+    /// it still points to its previous slot. The entries that were cascaded
+    /// ahead of time into slot 0 come from that stack's next slot, so they are
+    /// placed relative to it instead.
+    fn wrapped_now(
+        &self,
+        index: usize,
+        now: <E::Storage as Storage<E>>::Tick,
+    ) -> <E::Storage as Storage<E>>::Tick {
+        let above = index + 1;
+        if above >= self.stacks.len() {
+            return now;
+        }
+
+        let next = match self.stacks.get(above).current().checked_add(1) {
+            Some(next) => next,
+            None => return now,
+        };
+
+        let mut bytes = now.to_le_bytes();
+        bytes.as_mut()[above] = next;
+        <E::Storage as Storage<E>>::Tick::from_le_bytes(bytes)
+    }
+
+    fn record_cascades(&mut self, start: u64) {
+        let cascades = (self.cascades - start) as usize;
+        self.max_cascade = self.max_cascade.max(cascades);
+    }
+
+    fn tick_stacks(&mut self) -> Option<bool> {
         let mut can_skip = true;
         let mut is_empty = true;
         let mut has_pending = false;
 
         for index in 0..self.stacks.len() {
-            let (mut list, did_wrap) = self.stacks.get_mut(index).tick(can_skip);
+            let stack = self.stacks.get_mut(index);
+            let did_wrap = stack.tick(can_skip);
+            let slot = stack.current();
 
-            let now = self.ticks();
+            let mut now = self.ticks();
+            if did_wrap {
+                now = self.wrapped_now(index, now);
+            }
 
-            while let Some(entry) = list.pop() {
+            // the entries in the slot all move to lower stacks or the pending
+            // queue so they can be popped while cascading
+            let mut took = false;
+            while let Some(entry) = self.stacks.get_mut(index).pop_front(slot) {
+                took = true;
+                self.cascades += 1;
                 let start_tick = entry.start_tick();
-                if self.insert_at(entry, now, start_tick) {
+                let deadline = entry.deadline();
+                let to = self.insert_at(entry, now, start_tick);
+                self.observer.on_cascade(index, to, now, deadline);
+
+                if to.is_none() {
                     // A pending item is ready
                     has_pending = true;
                 } else {
@@ -193,11 +801,34 @@ impl<E: Entry> Wheel<E> {
                 return Some(has_pending);
             }
 
+            // entries taken from slot 0 as the stack wrapped were placed
+            // relative to the next slot of the stack above, so it has to step
+            // exactly one slot
+            if took {
+                can_skip = false;
+            }
+
             // children can only skip if this is also empty
             can_skip &= self.stacks.get(index).is_empty();
             is_empty &= can_skip;
         }
 
+        // the top stack wrapped so the overflowed entries are now in range
+        let mut overflow = self.overflow.take();
+        self.overflow_len = 0;
+        let now = self.ticks();
+        let from = self.stacks.len();
+
+        while let Some(entry) = overflow.pop() {
+            let start_tick = entry.start_tick();
+            let deadline = entry.deadline();
+            let to = self.insert_at(entry, now, start_tick);
+            self.observer.on_cascade(from, to, now, deadline);
+
+            has_pending |= to.is_none();
+            is_empty = false;
+        }
+
         if is_empty {
             return None;
         }
@@ -205,69 +836,702 @@ impl<E: Entry> Wheel<E> {
         Some(has_pending)
     }
 
-    /// Wakes all of the entries that have expires
-    pub fn wake<F: FnMut(E)>(&mut self, mut wake: F) -> usize {
-        let mut count = 0;
-
-        let mut pending = self.pending_wake.take();
+    /// Returns an iterator over all of the scheduled entries, along with their
+    /// absolute deadlines, in nondecreasing deadline order
+    ///
+    /// Entries that are pending a wake are yielded first. Slots in the higher
+    /// stacks cover a range of deadlines, so their entries are ordered as each
+    /// slot is reached. With the `alloc` feature the slot is collected and
+    /// sorted, which takes O(n log n) time overall and allocates room for the
+    /// largest slot. Without it the iterator doesn't allocate, but walks the
+    /// slot once per distinct deadline, which takes O(n * d) time for a slot of
+    /// `n` entries with `d` distinct deadlines and is slow for large slots.
+    pub fn iter(&self) -> Iter<'_, E, O> {
+        Iter {
+            wheel: self,
+            now: self.ticks(),
+            position: Position::Pending,
+            segment: None,
+        }
+    }
 
-        while let Some(entry) = pending.pop() {
-            count += 1;
-            wake(entry);
+    /// Captures the deadlines of all of the scheduled entries
+    ///
+    /// `payload` is called for each entry to produce a representation that can
+    /// outlive the wheel, e.g. to be persisted and restored in another process.
+    #[cfg(feature = "alloc")]
+    pub fn snapshot<P, F>(&self, payload: F) -> Snapshot<<E::Storage as Storage<E>>::Tick, P>
+    where
+        F: FnMut(&E) -> P,
+    {
+        let mut payload = payload;
+        let mut records = alloc::vec::Vec::with_capacity(self.len);
+        let mut push = |queue: &E::Queue| {
+            queue.for_each(|entry| {
+                records.push(Record {
+                    deadline: entry.deadline(),
+                    payload: payload(entry),
+                })
+            })
+        };
+
+        // the stacks are visited from the bottom so that entries with equal
+        // deadlines keep the order they are woken in
+        push(&self.pending_wake);
+        for index in 0..self.stacks.len() {
+            let stack = self.stacks.get(index);
+            let mut first = 0;
+            while let Some((slot, queue)) = stack.next_slot(first, u8::MAX) {
+                push(queue);
+                first = match slot.checked_add(1) {
+                    Some(next) => next,
+                    None => break,
+                };
+            }
         }
+        push(&self.overflow);
 
-        count
+        // pending entries are already in wake order
+        let now = self.ticks();
+        let pending = self.pending_len();
+        records[pending..].sort_by_key(|record| record.deadline.wrapping_sub(now));
+
+        Snapshot {
+            ticks: now,
+            pending,
+            records,
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::entry::atomic;
-    use alloc::{vec, vec::Vec};
-    use bolero::{check, generator::*};
-    use core::time::Duration;
-    use std::prelude::v1::*;
+    /// Schedules the entries captured in a [`Snapshot`]
+    ///
+    /// Each entry keeps the number of ticks it had remaining when the snapshot
+    /// was taken, measured from the wheel's current tick. Entries that were
+    /// pending a wake are immediately pending again. `entry` is called to
+    /// rebuild each entry from its payload.
+    #[cfg(feature = "alloc")]
+    pub fn restore<P, F>(
+        &mut self,
+        snapshot: Snapshot<<E::Storage as Storage<E>>::Tick, P>,
+        mut entry: F,
+    ) where
+        F: FnMut(P) -> E,
+    {
+        let now = self.ticks();
 
-    #[test]
-    fn size_snapshot() {
-        assert_eq!(core::mem::size_of::<Wheel<atomic::ArcEntry>>(), 33104);
+        for (index, record) in snapshot.records.into_iter().enumerate() {
+            let remaining = if index < snapshot.pending {
+                Default::default()
+            } else {
+                record.deadline.elapsed_since(snapshot.ticks)
+            };
+            let deadline = now.wrapping_add(remaining);
+
+            let mut entry = entry(record.payload);
+            let start_tick = deadline.wrapping_sub(entry.delay());
+            entry.set_start_tick(start_tick);
+            self.schedule(entry);
+        }
     }
 
-    #[test]
-    fn insert_advance_wake_check() {
-        let max_ticks = Duration::from_secs(1_000_000_000).as_nanos() as u64;
+    /// Moves the entries that match the predicate into a new wheel
+    ///
+    /// The new wheel starts at the same tick so each entry keeps its deadline,
+    /// and entries that are pending a wake remain pending.
+    pub fn split_off<F>(&mut self, mut f: F) -> Wheel<E>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        let mut other: Wheel<E> = Wheel::default();
+        other.set_cascade_budget(self.cascade_budget);
+
+        let bytes = self.ticks().to_le_bytes();
+        for (index, current) in bytes.as_ref().iter().enumerate() {
+            let stack = other.stacks.get_mut(index);
+            stack.set_current(*current);
+            other.len += self.stacks.get_mut(index).split_into(stack, &mut f);
+        }
 
-        let entry = gen::<Vec<u64>>().with().values(0..max_ticks);
-        let entries = gen::<Vec<_>>().with().values(entry);
+        let split = |from: &mut E::Queue, to: &mut E::Queue, f: &mut F| {
+            let mut moved = 0;
+            let mut queue = from.take();
+            while let Some(entry) = queue.pop() {
+                if f(&entry) {
+                    moved += 1;
+                    to.push(entry);
+                } else {
+                    from.push(entry);
+                }
+            }
+            moved
+        };
 
-        check!().with_generator(entries).for_each(|entries| {
-            test_helper(&entries[..]);
-        });
+        other.len += split(&mut self.pending_wake, &mut other.pending_wake, &mut f);
+
+        let moved = split(&mut self.overflow, &mut other.overflow, &mut f);
+        self.overflow_len -= moved;
+        other.overflow_len = moved;
+        other.len += moved;
+
+        self.len -= other.len;
+        other
     }
 
-    fn test_helper<T: AsRef<[u64]>>(entries: &[T]) {
-        let mut wheel = Wheel::default();
-        let mut sorted = vec![];
+    /// Moves all of the entries out of `other` and into this wheel
+    ///
+    /// The entries are rebased onto this wheel's current tick, keeping the
+    /// number of ticks each one had remaining in `other`. Entries that were
+    /// pending a wake in `other` are pending in this wheel.
+    pub fn append<P>(&mut self, other: &mut Wheel<E, P>)
+    where
+        P: WheelObserver<<E::Storage as Storage<E>>::Tick>,
+    {
+        let now = self.ticks();
+        let other_now = other.ticks();
 
-        let mut total_ticks = 0;
+        let rebase = |wheel: &mut Self, entry: E, remaining| {
+            let deadline = now.wrapping_add(remaining);
+            let mut entry = entry;
+            entry.set_start_tick(deadline.wrapping_sub(entry.delay()));
+            wheel.schedule(entry);
+        };
 
-        for entries in entries.iter().map(AsRef::as_ref) {
-            sorted.extend_from_slice(entries);
-            sorted.sort_unstable();
+        while let Some(entry) = other.pending_wake.pop() {
+            rebase(self, entry, Default::default());
+        }
 
-            let mut should_wake = false;
-            for entry in entries.iter().copied() {
-                // adding a 0-tick will immediately wake the entry
-                should_wake |= entry == 0;
-                wheel.insert(atomic::Entry::new(entry));
+        for index in 0..other.stacks.len() {
+            while let Some(slot) = other.stacks.get(index).first_occupied(0, u8::MAX) {
+                while let Some(entry) = other.stacks.get_mut(index).pop_front(slot) {
+                    let remaining = entry.deadline().wrapping_sub(other_now);
+                    rebase(self, entry, remaining);
+                }
             }
+        }
 
-            let mut sorted = sorted.drain(..);
-
-            let woken = wheel.wake(atomic::wake);
+        while let Some(entry) = other.overflow.pop() {
+            let remaining = entry.deadline().wrapping_sub(other_now);
+            rebase(self, entry, remaining);
+        }
 
-            assert_eq!(woken > 0, should_wake);
+        other.overflow_len = 0;
+        other.len = 0;
+    }
+
+    /// Wakes all of the entries that have expired
+    ///
+    /// Entries with equal deadlines are woken in the order they were inserted,
+    /// no matter which stacks they cascaded through on the way. Queues that
+    /// implement [`Queue::push_sorted`] may further order them by priority.
+    pub fn wake<F: FnMut(E)>(&mut self, mut wake: F) -> usize {
+        self.wake_with_info(|entry, _info| wake(entry))
+    }
+
+    /// Wakes all of the entries that have expired, passing along when each
+    /// entry was supposed to fire
+    pub fn wake_with_info<F>(&mut self, mut wake: F) -> usize
+    where
+        F: FnMut(E, WakeInfo<<E::Storage as Storage<E>>::Tick>),
+    {
+        let mut count = 0;
+
+        while let Some((entry, info)) = self.wake_one() {
+            count += 1;
+            wake(entry, info);
+        }
+
+        count
+    }
+
+    /// Advances the wheel to `now` and wakes all of the entries that are due
+    ///
+    /// This combines [`Wheel::advance_to`] and [`Wheel::wake`] for event loops
+    /// that read the time from a clock. A `now` at or before the current tick
+    /// doesn't move the wheel, so a stale clock reading can't wrap it around.
+    ///
+    /// Returns the number of woken entries along with the deadline of the
+    /// earliest entry that is still scheduled, which is when the wheel should
+    /// be polled next.
+    pub fn poll<F: FnMut(E)>(
+        &mut self,
+        now: <E::Storage as Storage<E>>::Tick,
+        wake: F,
+    ) -> Poll<<E::Storage as Storage<E>>::Tick> {
+        if now > self.ticks() {
+            self.advance_to(now);
+        }
+
+        let woken = self.wake(wake);
+
+        Poll {
+            woken,
+            next_deadline: self.earliest_deadline(),
+        }
+    }
+
+    /// Wakes at most `limit` of the entries that have expired
+    ///
+    /// The remaining entries stay pending, in order, for the next call. This
+    /// allows a large burst of expirations to be interleaved with other work.
+    ///
+    /// Returns the number of entries that were woken
+    pub fn wake_up_to<F: FnMut(E)>(&mut self, limit: usize, mut wake: F) -> usize {
+        let mut count = 0;
+
+        while count < limit {
+            if let Some((entry, _info)) = self.wake_one() {
+                count += 1;
+                wake(entry);
+            } else {
+                break;
+            }
+        }
+
+        count
+    }
+
+    /// Returns an iterator that pops the entries that have expired
+    ///
+    /// Entries are popped lazily, so any that haven't been consumed when the
+    /// iterator is dropped remain pending for a later call.
+    pub fn expired(&mut self) -> Expired<'_, E, O> {
+        Expired { wheel: self }
+    }
+
+    /// Returns `true` if there are expired entries waiting to be woken
+    pub fn has_pending(&self) -> bool {
+        !self.pending_wake.is_empty()
+    }
+
+    /// Pops a single expired entry off of the pending queue
+    pub(crate) fn wake_one(&mut self) -> Option<Woken<E>> {
+        let entry = self.pending_wake.pop()?;
+        let now = self.ticks();
+        let info = WakeInfo::new(entry.deadline(), now);
+
+        self.len -= 1;
+        self.woken += 1;
+        self.observer.on_wake(now, info.deadline);
+
+        Some((entry, info))
+    }
+}
+
+/// Timing information for a woken entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WakeInfo<T> {
+    /// The tick at which the entry was scheduled to expire
+    pub deadline: T,
+    /// The current tick of the wheel
+    pub now: T,
+    /// The number of ticks between the deadline and when the entry was woken
+    pub lateness: T,
+}
+
+impl<T: Tick> WakeInfo<T> {
+    fn new(deadline: T, now: T) -> Self {
+        Self {
+            deadline,
+            now,
+            lateness: now.elapsed_since(deadline),
+        }
+    }
+}
+
+/// The result of [`Wheel::poll`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Poll<T> {
+    /// The number of entries that were woken
+    pub woken: usize,
+    /// The tick at which the earliest remaining entry expires
+    pub next_deadline: Option<T>,
+}
+
+/// Iterator over the expired entries in a [`Wheel`]
+///
+/// Created by [`Wheel::expired`].
+pub struct Expired<'a, E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick>> {
+    wheel: &'a mut Wheel<E, O>,
+}
+
+impl<E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick>> Iterator for Expired<'_, E, O> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        let (entry, _info) = self.wheel.wake_one()?;
+        Some(entry)
+    }
+}
+
+/// Occupancy and activity counters for a [`Wheel`]
+///
+/// Created by [`Wheel::stats`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    /// The total number of entries in the wheel
+    pub len: usize,
+    /// The number of entries that have expired and are waiting to be woken
+    pub pending: usize,
+    /// The number of entries that expire after the tick range wraps around
+    pub overflow: usize,
+    /// The number of times an entry was moved out of a stack slot and re-inserted
+    pub cascades: u64,
+    /// The largest number of entries that were re-inserted by a single step of the wheel
+    pub max_cascade: usize,
+    /// The number of entries that have been woken
+    pub woken: u64,
+}
+
+/// Occupancy counters for a single stack in a [`Wheel`]
+///
+/// Created by [`Wheel::stack_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StackStats {
+    /// The number of entries in the stack
+    pub entries: usize,
+    /// The number of slots in the stack that contain at least one entry
+    pub occupied_slots: u16,
+}
+
+/// Iterator over the scheduled entries in a [`Wheel`]
+///
+/// Created by [`Wheel::iter`].
+pub struct Iter<'a, E: Entry, O: WheelObserver<<E::Storage as Storage<E>>::Tick>> {
+    wheel: &'a Wheel<E, O>,
+    now: <E::Storage as Storage<E>>::Tick,
+    position: Position,
+    segment: Option<SegmentIter<'a, E>>,
+}
+
+/// The next group of queues that [`Iter`] visits
+#[derive(Clone, Copy)]
+enum Position {
+    Pending,
+    /// The slots in a stack from `slot` up to the end of the rotation
+    Ahead {
+        stack: usize,
+        slot: u16,
+    },
+    /// The slots in a stack at or behind the cursor
+    Behind {
+        stack: usize,
+    },
+    Done,
+}
+
+impl<'a, E, O> Iter<'a, E, O>
+where
+    E: Entry,
+    O: WheelObserver<<E::Storage as Storage<E>>::Tick>,
+{
+    fn ahead(&self, stack: usize) -> Position {
+        // the first slot ahead of the cursor in the higher stacks is visited
+        // along with the slots behind the cursor of the stack below
+        let skip = if stack == 0 { 1 } else { 2 };
+        let slot = self.wheel.stacks.get(stack).current() as u16 + skip;
+        Position::Ahead { stack, slot }
+    }
+
+    fn next_segment(&mut self) -> Option<Segment<'a, E>> {
+        let wheel = self.wheel;
+        let stacks = &wheel.stacks;
+
+        loop {
+            match self.position {
+                Position::Pending => {
+                    self.position = self.ahead(0);
+                    if !wheel.pending_wake.is_empty() {
+                        // pending entries are already in wake order
+                        return Some(Segment {
+                            stacks,
+                            slots: None,
+                            extra: Some(&wheel.pending_wake),
+                            sorted: false,
+                        });
+                    }
+                }
+                Position::Ahead { stack, slot } => {
+                    let next = u8::try_from(slot)
+                        .ok()
+                        .and_then(|slot| stacks.get(stack).next_slot(slot, u8::MAX));
+
+                    match next {
+                        Some((index, _)) => {
+                            self.position = Position::Ahead {
+                                stack,
+                                slot: index as u16 + 1,
+                            };
+                            return Some(Segment {
+                                stacks,
+                                slots: Some((stack, index as u16, index)),
+                                extra: None,
+                                sorted: true,
+                            });
+                        }
+                        None => self.position = Position::Behind { stack },
+                    }
+                }
+                Position::Behind { stack } => {
+                    // slots behind the cursor hold entries that were cascaded
+                    // ahead of time from the next slot of the higher stack, so
+                    // the two are ordered together. Entries in the overflow list
+                    // expire after everything in the top stack.
+                    let extra = if stack + 1 < stacks.len() {
+                        self.position = self.ahead(stack + 1);
+                        let higher = stacks.get(stack + 1);
+                        higher
+                            .current()
+                            .checked_add(1)
+                            .and_then(|next| higher.next_slot(next, next))
+                            .map(|(_, queue)| queue)
+                    } else {
+                        self.position = Position::Done;
+                        Some(&wheel.overflow)
+                    };
+
+                    let current = stacks.get(stack).current();
+                    return Some(Segment {
+                        stacks,
+                        slots: Some((stack, 0, current)),
+                        extra,
+                        sorted: true,
+                    });
+                }
+                Position::Done => return None,
+            }
+        }
+    }
+}
+
+impl<'a, E, O> Iterator for Iter<'a, E, O>
+where
+    E: Entry,
+    O: WheelObserver<<E::Storage as Storage<E>>::Tick>,
+{
+    type Item = (EntryRef<'a, E>, <E::Storage as Storage<E>>::Tick);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(segment) = self.segment.as_mut() {
+                if let Some(entry) = segment.next() {
+                    let deadline = entry.deadline();
+                    return Some((entry, deadline));
+                }
+            }
+
+            let segment = self.next_segment()?;
+            self.segment = Some(SegmentIter::new(segment, self.now));
+        }
+    }
+}
+
+/// A set of queues whose entries [`Iter`] yields together
+struct Segment<'a, E: Entry> {
+    stacks: &'a E::Storage,
+    /// The occupied slots of a stack between two positions
+    slots: Option<(usize, u16, u8)>,
+    extra: Option<&'a E::Queue>,
+    /// Whether the entries need to be ordered by deadline
+    sorted: bool,
+}
+
+impl<E: Entry> Clone for Segment<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: Entry> Copy for Segment<'_, E> {}
+
+impl<'a, E: Entry> Segment<'a, E> {
+    fn entries(self) -> SegmentEntries<'a, E> {
+        SegmentEntries {
+            segment: self,
+            queue: None,
+        }
+    }
+}
+
+/// Walks the entries in a [`Segment`] in queue order
+struct SegmentEntries<'a, E: Entry> {
+    segment: Segment<'a, E>,
+    queue: Option<<E::Queue as Queue<E>>::Iter<'a>>,
+}
+
+impl<'a, E: Entry + 'a> Iterator for SegmentEntries<'a, E> {
+    type Item = EntryRef<'a, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.queue.as_mut().and_then(Iterator::next) {
+                return Some(entry);
+            }
+
+            let segment = &mut self.segment;
+            let slot = segment.slots.and_then(|(stack, first, last)| {
+                let first = u8::try_from(first).ok().filter(|first| *first <= last)?;
+                let (index, queue) = segment.stacks.get(stack).next_slot(first, last)?;
+                Some((stack, index, last, queue))
+            });
+
+            let queue = match slot {
+                Some((stack, index, last, queue)) => {
+                    segment.slots = Some((stack, index as u16 + 1, last));
+                    queue
+                }
+                None => {
+                    segment.slots = None;
+                    segment.extra.take()?
+                }
+            };
+
+            self.queue = Some(queue.iter());
+        }
+    }
+}
+
+/// Yields the entries in a [`Segment`], ordered by deadline if needed
+enum SegmentIter<'a, E: Entry> {
+    /// The entries are already in order
+    Unsorted(SegmentEntries<'a, E>),
+    /// The entries were collected and sorted by deadline
+    #[cfg(feature = "alloc")]
+    Sorted(alloc::vec::IntoIter<EntryRef<'a, E>>),
+    /// The entries are ordered by walking the segment once per distinct
+    /// deadline, which doesn't allocate
+    #[cfg(any(test, not(feature = "alloc")))]
+    Selected {
+        segment: Segment<'a, E>,
+        entries: SegmentEntries<'a, E>,
+        now: <E::Storage as Storage<E>>::Tick,
+        /// The distance from `now` of the entries being yielded
+        target: Option<<E::Storage as Storage<E>>::Tick>,
+    },
+}
+
+impl<'a, E: Entry + 'a> SegmentIter<'a, E> {
+    #[cfg(feature = "alloc")]
+    fn new(segment: Segment<'a, E>, now: <E::Storage as Storage<E>>::Tick) -> Self {
+        if !segment.sorted {
+            return Self::Unsorted(segment.entries());
+        }
+
+        // the sort is stable so entries with equal deadlines stay in order
+        let mut entries: alloc::vec::Vec<_> = segment.entries().collect();
+        entries.sort_by_key(|entry| entry.deadline().wrapping_sub(now));
+        Self::Sorted(entries.into_iter())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn new(segment: Segment<'a, E>, now: <E::Storage as Storage<E>>::Tick) -> Self {
+        if !segment.sorted {
+            return Self::Unsorted(segment.entries());
+        }
+
+        Self::selected(segment, now)
+    }
+
+    #[cfg(any(test, not(feature = "alloc")))]
+    fn selected(segment: Segment<'a, E>, now: <E::Storage as Storage<E>>::Tick) -> Self {
+        Self::Selected {
+            segment,
+            entries: segment.entries(),
+            now,
+            target: None,
+        }
+    }
+
+    fn next(&mut self) -> Option<EntryRef<'a, E>> {
+        match self {
+            Self::Unsorted(entries) => entries.next(),
+            #[cfg(feature = "alloc")]
+            Self::Sorted(entries) => entries.next(),
+            #[cfg(any(test, not(feature = "alloc")))]
+            Self::Selected {
+                segment,
+                entries,
+                now,
+                target,
+            } => {
+                let now = *now;
+                let distance = |entry: &E| entry.deadline().wrapping_sub(now);
+
+                loop {
+                    if let Some(target) = *target {
+                        for entry in entries.by_ref() {
+                            if distance(&entry) == target {
+                                return Some(entry);
+                            }
+                        }
+                    }
+
+                    // find the closest deadline that hasn't been yielded yet
+                    let bound = *target;
+                    let closest = segment
+                        .entries()
+                        .map(|entry| distance(&entry))
+                        .filter(|distance| bound.is_none_or(|bound| *distance > bound))
+                        .min()?;
+
+                    *target = Some(closest);
+                    *entries = segment.entries();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::atomic;
+    use alloc::{sync::Arc, vec, vec::Vec};
+    use bolero::{check, generator::*, RngEngine, TestTarget};
+    use core::time::Duration;
+
+    /// The number of inputs for tests that need more than bolero's default
+    /// to reach the cascade budget's edge cases
+    const ITERATIONS: usize = 10_000;
+
+    #[test]
+    fn size_snapshot() {
+        assert_eq!(core::mem::size_of::<Wheel<atomic::ArcEntry>>(), 33296);
+    }
+
+    #[test]
+    fn insert_advance_wake_check() {
+        let max_ticks = Duration::from_secs(1_000_000_000).as_nanos() as u64;
+
+        let entry = gen::<Vec<u64>>().with().values(0..max_ticks);
+        let entries = gen::<Vec<_>>().with().values(entry);
+
+        check!().with_generator(entries).for_each(|entries| {
+            test_helper(&entries[..]);
+        });
+    }
+
+    fn test_helper<T: AsRef<[u64]>>(entries: &[T]) {
+        let mut wheel = Wheel::default();
+        let mut sorted = vec![];
+
+        let mut total_ticks = 0;
+
+        for entries in entries.iter().map(AsRef::as_ref) {
+            sorted.extend_from_slice(entries);
+            sorted.sort_unstable();
+
+            let mut should_wake = false;
+            for entry in entries.iter().copied() {
+                // adding a 0-tick will immediately wake the entry
+                should_wake |= entry == 0;
+                wheel.insert(atomic::Entry::new(entry));
+            }
+
+            let mut sorted = sorted.drain(..);
+
+            let woken = wheel.wake(atomic::wake);
+
+            assert_eq!(woken > 0, should_wake);
 
             for _ in 0..woken {
                 sorted.next();
@@ -277,14 +1541,13 @@ mod tests {
 
             while let Some(expected) = sorted.next() {
                 let delta = expected - elapsed;
-                // TODO
-                //let next_delta = wheel.next_delta().unwrap();
-                //assert!(
-                //    1 <= next_delta && next_delta <= delta,
-                //    "delta: {}, next_delta(): {}",
-                //    delta,
-                //    next_delta
-                //);
+                let next_delta = wheel.next_delta().unwrap();
+                assert!(
+                    1 <= next_delta && next_delta <= delta,
+                    "delta: {}, next_delta(): {}",
+                    delta,
+                    next_delta
+                );
                 assert_eq!(wheel.skip(), Some(delta));
                 elapsed += delta;
 
@@ -307,13 +1570,1135 @@ mod tests {
             assert_eq!(wheel.skip(), None);
             assert_eq!(wheel.wake(atomic::wake), 0);
             assert!(wheel.is_empty());
+            assert_eq!(wheel.len(), 0);
 
-            total_ticks += elapsed;
+            total_ticks = total_ticks.wrapping_add(elapsed);
 
             assert_eq!(wheel.ticks(), total_ticks);
         }
     }
 
+    #[test]
+    fn iter_test() {
+        let max_ticks = Duration::from_secs(1_000_000).as_nanos() as u64;
+
+        let entry = gen::<Vec<u64>>().with().values(0..max_ticks);
+        let skips = gen::<u8>();
+
+        check!()
+            .with_generator((entry, skips))
+            .for_each(|(entries, skips)| {
+                let mut wheel = Wheel::default();
+
+                for delay in entries.iter().copied() {
+                    wheel.insert(atomic::Entry::new(delay));
+                }
+
+                for _ in 0..(*skips % 4) {
+                    wheel.skip();
+                }
+
+                let mut expected = entries.to_vec();
+                expected.sort_unstable();
+
+                let actual: Vec<_> = wheel.iter().map(|(_, deadline)| deadline).collect();
+                assert_eq!(expected, actual);
+
+                // all of the entries were inserted at tick 0
+                for (entry, deadline) in wheel.iter() {
+                    assert_eq!(entry.delay(), deadline);
+                }
+            });
+    }
+
+    #[test]
+    fn iter_selection_test() {
+        // a few deadlines repeated across many entries
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 20));
+
+        check!()
+            .with_generator((delays, gen::<u8>(), 0..(1u64 << 18)))
+            .for_each(|(delays, budget, target)| {
+                let mut wheel = Wheel::default();
+                wheel.set_cascade_budget(*budget as usize);
+                for delay in delays.iter().copied() {
+                    wheel.insert(atomic::Entry::new(delay & !0xff));
+                    wheel.insert(atomic::Entry::new(delay));
+                }
+                wheel.advance_to(*target);
+
+                // the allocation-free ordering matches the sorted one
+                let now = wheel.ticks();
+                let mut iter = wheel.iter();
+                while let Some(segment) = iter.next_segment() {
+                    if !segment.sorted {
+                        continue;
+                    }
+
+                    let collect = |mut segment: SegmentIter<'_, _>| {
+                        let mut entries = vec![];
+                        while let Some(entry) = segment.next() {
+                            entries.push(Arc::as_ptr(&entry));
+                        }
+                        entries
+                    };
+                    assert_eq!(
+                        collect(SegmentIter::new(segment, now)),
+                        collect(SegmentIter::selected(segment, now))
+                    );
+                }
+            });
+    }
+
+    #[test]
+    fn iter_cascade_budget_test() {
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 26));
+
+        check!()
+            .with_generator((delays, gen::<u8>(), 0..(1u64 << 24)))
+            .for_each(|(delays, budget, target)| {
+                let mut wheel = Wheel::default();
+                wheel.set_cascade_budget(*budget as usize);
+
+                for delay in delays.iter().copied() {
+                    wheel.insert(atomic::Entry::new(delay));
+                }
+
+                // entries may now be cascaded behind the cursors
+                wheel.advance_to(*target);
+
+                let actual: Vec<_> = wheel.iter().map(|(_, deadline)| deadline).collect();
+                let pending = delays.iter().filter(|delay| **delay <= *target).count();
+                assert_eq!(actual.len(), delays.len());
+                assert!(actual[..pending]
+                    .iter()
+                    .all(|deadline| *deadline <= *target));
+                assert!(actual[pending..].windows(2).all(|w| w[0] <= w[1]));
+
+                let mut expected = delays.to_vec();
+                expected.sort_unstable();
+                let mut sorted = actual;
+                sorted.sort_unstable();
+                assert_eq!(expected, sorted);
+            });
+    }
+
+    #[test]
+    fn len_stats_test() {
+        let mut wheel = Wheel::default();
+        let entries: Vec<_> = [0, 1, 2, 300, 300, 70_000]
+            .iter()
+            .map(|delay| atomic::Entry::new(*delay))
+            .collect();
+
+        for entry in entries.iter() {
+            wheel.insert(entry.clone());
+        }
+
+        assert_eq!(wheel.len(), 6);
+        let stats = wheel.stats();
+        assert_eq!(stats.len, 6);
+        assert_eq!(stats.pending, 1);
+        let scheduled: usize = wheel.stack_stats().map(|s| s.entries).sum();
+        assert_eq!(scheduled, 5);
+        assert_eq!(wheel.stack_stats().len(), 8);
+
+        assert!(wheel.cancel(&entries[3]).is_some());
+        assert!(wheel.cancel(&entries[3]).is_none());
+        assert!(wheel.cancel(&entries[0]).is_some());
+        assert_eq!(wheel.len(), 4);
+        assert_eq!(wheel.stats().pending, 0);
+
+        let mut woken = 0;
+        while wheel.skip().is_some() {
+            woken += wheel.wake(atomic::wake);
+            assert_eq!(wheel.len(), 4 - woken);
+        }
+
+        let stats = wheel.stats();
+        assert_eq!(woken, 4);
+        assert_eq!(stats.len, 0);
+        assert_eq!(stats.woken, 4);
+        assert!(stats.cascades > 0);
+        assert!(wheel.stack_stats().all(|s| s == StackStats::default()));
+    }
+
+    #[derive(Debug, Default)]
+    struct Counts {
+        inserts: usize,
+        cascades: usize,
+        wakes: usize,
+        cancels: usize,
+        advanced: u64,
+        lateness: u64,
+    }
+
+    impl WheelObserver<u64> for Counts {
+        fn on_insert(&mut self, _stack: Option<usize>, _now: u64, _deadline: u64) {
+            self.inserts += 1;
+        }
+
+        fn on_cascade(&mut self, from: usize, to: Option<usize>, _now: u64, _deadline: u64) {
+            if let Some(to) = to {
+                assert_ne!(from, to);
+            }
+            self.cascades += 1;
+        }
+
+        fn on_wake(&mut self, now: u64, deadline: u64) {
+            self.wakes += 1;
+            self.lateness += now - deadline;
+        }
+
+        fn on_cancel(&mut self, _stack: Option<usize>, _now: u64, _deadline: u64) {
+            self.cancels += 1;
+        }
+
+        fn on_advance(&mut self, from: u64, to: u64) {
+            assert!(from < to);
+            self.advanced += to - from;
+        }
+    }
+
+    #[test]
+    fn observer_test() {
+        let mut wheel = Wheel::with_observer(Counts::default());
+        let cancelled = atomic::Entry::new(5);
+
+        for delay in [0, 1, 256, 300, 70_000].iter() {
+            wheel.insert(atomic::Entry::new(*delay));
+        }
+        wheel.insert(cancelled.clone());
+        assert!(wheel.cancel(&cancelled).is_some());
+
+        while wheel.skip().is_some() {
+            wheel.wake(atomic::wake);
+        }
+
+        let counts = wheel.observer();
+        assert_eq!(counts.inserts, 6);
+        assert_eq!(counts.cancels, 1);
+        assert_eq!(counts.wakes, 5);
+        assert_eq!(counts.lateness, 0);
+        assert_eq!(counts.advanced, 70_000);
+        assert_eq!(counts.cascades as u64, wheel.stats().cascades);
+    }
+
+    #[test]
+    fn wake_info_test() {
+        let mut wheel = Wheel::default();
+
+        for delay in [0, 5, 5, 1000].iter() {
+            wheel.insert(atomic::Entry::new(*delay));
+        }
+
+        let mut woken = vec![];
+        loop {
+            wheel.wake_with_info(|entry, info| {
+                assert_eq!(info.deadline, entry.deadline());
+                woken.push(info);
+            });
+
+            if wheel.skip().is_none() {
+                break;
+            }
+        }
+
+        let expected: Vec<_> = [0, 5, 5, 1000]
+            .iter()
+            .map(|deadline| WakeInfo {
+                deadline: *deadline,
+                now: *deadline,
+                lateness: 0,
+            })
+            .collect();
+        assert_eq!(woken, expected);
+    }
+
+    #[test]
+    fn snapshot_order_test() {
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 20));
+
+        check!()
+            .with_generator((delays, gen::<u8>(), 0..(1u64 << 18)))
+            .for_each(|(delays, budget, target)| {
+                let mut wheel = Wheel::default();
+                wheel.set_cascade_budget(*budget as usize);
+                for delay in delays.iter().copied() {
+                    wheel.insert(atomic::Entry::new(delay & !0xff));
+                    wheel.insert(atomic::Entry::new(delay));
+                }
+                wheel.advance_to(*target);
+
+                let snapshot = wheel.snapshot(Arc::as_ptr);
+                let records: Vec<_> = snapshot
+                    .records
+                    .iter()
+                    .map(|record| (record.payload, record.deadline))
+                    .collect();
+                let expected: Vec<_> = wheel
+                    .iter()
+                    .map(|(entry, deadline)| (Arc::as_ptr(&entry), deadline))
+                    .collect();
+                assert_eq!(records, expected);
+                assert_eq!(snapshot.pending, wheel.stats().pending);
+            });
+    }
+
+    #[test]
+    fn snapshot_restore_test() {
+        let mut wheel = Wheel::default();
+
+        for delay in [0, 3, 300, 70_000].iter() {
+            wheel.insert(atomic::Entry::new(*delay));
+        }
+        assert_eq!(wheel.skip(), Some(0));
+        assert_eq!(wheel.skip(), Some(0));
+        wheel.wake(atomic::wake);
+        assert_eq!(wheel.skip(), Some(3));
+
+        let snapshot = wheel.snapshot(|entry| entry.delay());
+        assert_eq!(snapshot.ticks, 3);
+        assert_eq!(snapshot.pending, 1);
+        let deadlines: Vec<_> = snapshot.records.iter().map(|r| r.deadline).collect();
+        assert_eq!(deadlines, [3, 300, 70_000]);
+
+        // restore into a wheel that is at a different tick
+        let mut restored = Wheel::default();
+        restored.insert(atomic::Entry::new(1000));
+        assert_eq!(restored.skip(), Some(1000));
+        restored.wake(atomic::wake);
+
+        restored.restore(snapshot, atomic::Entry::new);
+        assert_eq!(restored.len(), 3);
+
+        let deadlines: Vec<_> = restored.iter().map(|(_, deadline)| deadline).collect();
+        assert_eq!(deadlines, [1000, 1297, 70_997]);
+
+        assert_eq!(restored.wake(atomic::wake), 1);
+        assert_eq!(restored.skip(), Some(297));
+        assert_eq!(restored.wake(atomic::wake), 1);
+        assert_eq!(restored.skip(), Some(69_700));
+        assert_eq!(restored.wake(atomic::wake), 1);
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn insert_deadline_test() {
+        let mut wheel = Wheel::default();
+        wheel.advance_to(100);
+
+        for (delay, deadline) in [(10, 50), (0, 100), (200, 150), (5, 70_000)].iter() {
+            wheel.insert_deadline(atomic::Entry::new(*delay), *deadline);
+        }
+
+        assert_eq!(wheel.len(), 4);
+        let deadlines: Vec<_> = wheel.iter().map(|(_, deadline)| deadline).collect();
+        assert_eq!(deadlines, [50, 100, 150, 70_000]);
+
+        let mut lateness = vec![];
+        loop {
+            wheel.wake_with_info(|_, info| lateness.push((info.deadline, info.lateness)));
+            if wheel.skip().is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(lateness, [(50, 50), (100, 0), (150, 0), (70_000, 0)]);
+        assert_eq!(wheel.ticks(), 70_000);
+    }
+
+    #[test]
+    fn overflow_list_test() {
+        let mut wheel = Wheel::default();
+        let start = u64::MAX - 10;
+        wheel.advance_to(start);
+
+        assert_eq!(
+            wheel.try_insert(atomic::Entry::new(20)),
+            Err(Error::DeadlineOutOfRange)
+        );
+        assert!(wheel.is_empty());
+
+        wheel.insert(atomic::Entry::new(5));
+        wheel.insert(atomic::Entry::new(20));
+        let cancelled = atomic::Entry::new(30);
+        wheel.insert(cancelled.clone());
+
+        let stats = wheel.stats();
+        assert_eq!(stats.len, 3);
+        assert_eq!(stats.overflow, 2);
+        let deadlines: Vec<_> = wheel.iter().map(|(_, deadline)| deadline).collect();
+        assert_eq!(deadlines, [start + 5, 9, 19]);
+
+        assert!(wheel.cancel(&cancelled).is_some());
+        assert_eq!(wheel.stats().overflow, 1);
+
+        let mut woken = vec![];
+        loop {
+            wheel.wake_with_info(|_, info| woken.push(info));
+            if wheel.skip().is_none() {
+                break;
+            }
+        }
+
+        let woken: Vec<_> = woken
+            .iter()
+            .map(|info| (info.deadline, info.lateness))
+            .collect();
+        assert_eq!(woken, [(start + 5, 0), (9, 0)]);
+        assert_eq!(wheel.ticks(), 9);
+        assert_eq!(wheel.stats().overflow, 0);
+    }
+
+    #[test]
+    fn try_test() {
+        let mut wheel = Wheel::default();
+        wheel.advance_to(100);
+
+        let entry = atomic::Entry::new(10);
+        assert_eq!(wheel.try_insert(entry.clone()), Ok(()));
+        assert_eq!(
+            wheel.try_insert(entry.clone()),
+            Err(Error::AlreadyScheduled)
+        );
+        assert_eq!(
+            wheel.try_insert_deadline(entry.clone(), 200),
+            Err(Error::AlreadyScheduled)
+        );
+        assert_eq!(
+            wheel.try_insert_deadline(atomic::Entry::new(0), 99),
+            Err(Error::DeadlineInPast)
+        );
+        assert_eq!(
+            wheel.try_insert_deadline(atomic::Entry::new(0), 100),
+            Ok(())
+        );
+        assert_eq!(wheel.len(), 2);
+
+        assert_eq!(wheel.try_advance_to(50), Err(Error::TimeWentBackwards));
+        assert_eq!(wheel.try_advance_to(105), Ok(5));
+        assert_eq!(wheel.try_reschedule(&entry), Ok(()));
+        assert_eq!(entry.deadline(), 115);
+
+        assert!(wheel.cancel(&entry).is_some());
+        assert_eq!(wheel.try_reschedule(&entry), Err(Error::NotScheduled));
+
+        wheel.advance_to(u64::MAX - 5);
+        let far = atomic::Entry::new(10);
+        wheel.insert_deadline(far.clone(), u64::MAX);
+        assert_eq!(wheel.try_reschedule(&far), Err(Error::DeadlineOutOfRange));
+        assert_eq!(far.deadline(), u64::MAX);
+    }
+
+    #[test]
+    fn double_insert_test() {
+        let mut wheel = Wheel::default();
+        let entry = atomic::Entry::new(10);
+
+        wheel.insert(entry.clone());
+        wheel.advance_to(5);
+        wheel.insert(entry.clone());
+        assert_eq!(wheel.len(), 1);
+        assert_eq!(entry.deadline(), 15);
+
+        // an expired entry that hasn't been woken yet is scheduled again
+        wheel.advance_to(15);
+        wheel.insert_deadline(entry.clone(), 20);
+        assert_eq!(wheel.len(), 1);
+        assert_eq!(wheel.wake(atomic::wake), 0);
+
+        assert_eq!(wheel.skip(), Some(5));
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "already scheduled in another wheel")]
+    fn double_insert_other_wheel_test() {
+        let entry = atomic::Entry::new(10);
+        let mut a = Wheel::default();
+        let mut b = Wheel::default();
+
+        a.insert(entry.clone());
+        b.insert(entry);
+    }
+
+    #[test]
+    fn static_test() {
+        use crate::entry::fixed;
+        use std::sync::Mutex;
+
+        static WHEEL: Mutex<Wheel<fixed::StaticEntry>> = Mutex::new(Wheel::new());
+        static A: fixed::Entry = fixed::Entry::new(300);
+        static B: fixed::Entry = fixed::Entry::new(10);
+        static C: fixed::Entry = fixed::Entry::new(u32::MAX);
+
+        let mut wheel = WHEEL.lock().unwrap();
+        for entry in [&A, &B, &C].iter().copied() {
+            wheel.insert(entry);
+        }
+        assert!(C.is_scheduled());
+        assert!(wheel.cancel(&&C).is_some());
+        assert!(!C.is_scheduled());
+
+        let mut woken = vec![];
+        loop {
+            wheel.wake(|entry| woken.push((entry.deadline(), entry.is_scheduled())));
+            if wheel.skip().is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(woken, [(10, false), (300, false)]);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn next_alarm_test() {
+        let mut wheel = Wheel::default();
+        assert_eq!(wheel.next_alarm(1000), 1000);
+
+        wheel.advance_to(10);
+        wheel.insert(atomic::Entry::new(70_000));
+        wheel.insert(atomic::Entry::new(300));
+        wheel.insert(atomic::Entry::new(290));
+
+        // the slot for the entries covers 256 ticks but the alarm is exact
+        assert_eq!(wheel.next_delta(), Some(246));
+        assert_eq!(wheel.next_alarm(1000), 290);
+        assert_eq!(wheel.next_alarm(100), 100);
+
+        wheel.catch_up(290);
+        assert_eq!(wheel.next_alarm(1000), 0);
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert_eq!(wheel.next_alarm(1000), 10);
+
+        wheel.catch_up(10);
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert_eq!(wheel.next_alarm(1 << 20), 70_010 - 310);
+    }
+
+    #[test]
+    fn catch_up_test() {
+        let entry = gen::<Vec<u64>>().with().values(0..(1u64 << 40));
+        let entries = gen::<Vec<_>>().with().values(entry);
+        let ops = (gen::<u64>(), entries);
+
+        check!().with_generator(ops).for_each(|(seed, entries)| {
+            let mut expected = Wheel::default();
+            let mut actual = Wheel::default();
+            let budget = [0, 1, 16][(seed % 3) as usize];
+            expected.set_cascade_budget(budget);
+            actual.set_cascade_budget(budget);
+
+            for (round, delays) in entries.iter().enumerate() {
+                for delay in delays.iter().copied() {
+                    expected.insert(atomic::Entry::new(delay));
+                    actual.insert(atomic::Entry::new(delay));
+                }
+
+                // step both wheels for a while so entries get cascaded ahead
+                // of time before catching up
+                for _ in 0..(seed >> (round % 64)) % 512 {
+                    let now = expected.ticks() + 1;
+                    expected.advance_to(now);
+                    actual.advance_to(now);
+                }
+
+                let elapsed = seed.rotate_left(round as u32 * 7) >> (seed % 64).max(20);
+                let target = expected.ticks() + elapsed;
+                expected.advance_to(target);
+                actual.catch_up(elapsed);
+
+                assert_eq!(actual.ticks(), target);
+                assert_eq!(actual.len(), expected.len());
+                assert_eq!(actual.stats().pending, expected.stats().pending);
+
+                let deadlines = |wheel: &Wheel<atomic::ArcEntry>| {
+                    let mut deadlines: Vec<_> = wheel.iter().map(|(_, d)| d).collect();
+                    deadlines.sort_unstable();
+                    deadlines
+                };
+                assert_eq!(deadlines(&actual), deadlines(&expected));
+            }
+
+            // the entries that weren't due yet are still placed correctly
+            let pending = actual.stats().pending;
+            let mut woken = vec![];
+            loop {
+                actual.wake_with_info(|_, info| woken.push(info.lateness));
+                if actual.skip().is_none() {
+                    break;
+                }
+            }
+            assert!(woken.iter().skip(pending).all(|l| *l == 0));
+        });
+    }
+
+    #[test]
+    fn priority_test() {
+        use crate::entry::fixed;
+
+        fn entry(delay: u32, priority: u32) -> fixed::StaticEntry {
+            alloc::boxed::Box::leak(alloc::boxed::Box::new(fixed::Entry::with_priority(
+                delay, priority,
+            )))
+        }
+
+        let mut wheel = Wheel::<fixed::StaticEntry>::new();
+        let entries = [
+            entry(300, 2),
+            entry(300, 1),
+            entry(290, 5),
+            entry(300, 2),
+            entry(300, 0),
+            entry(70_000, 0),
+        ];
+        for entry in entries.iter().copied() {
+            wheel.insert(entry);
+        }
+
+        // catching up takes the whole slot at once so the pending queue has
+        // to order the entries itself
+        wheel.catch_up(1000);
+
+        let late = entry(0, 3);
+        wheel.insert_deadline(late, 300);
+
+        let mut woken = vec![];
+        wheel.wake(|entry| woken.push(entry as *const _));
+
+        let expected: Vec<_> = [2, 4, 1, 0, 3]
+            .iter()
+            .map(|index| entries[*index] as *const _)
+            .chain(Some(late as *const _))
+            .collect();
+        assert_eq!(woken, expected);
+        assert_eq!(wheel.len(), 1);
+    }
+
+    #[test]
+    fn fixed_set_delay_test() {
+        use crate::entry::fixed;
+
+        static ENTRY: fixed::Entry = fixed::Entry::new(10);
+
+        let mut wheel = Wheel::<fixed::StaticEntry>::new();
+        wheel.insert(&ENTRY);
+
+        // the wheel still finds the entry by the deadline it was inserted with
+        assert_eq!(ENTRY.set_delay(20), Err(Error::AlreadyScheduled));
+        assert!(wheel.cancel(&&ENTRY).is_some());
+
+        assert_eq!(ENTRY.set_delay(20), Ok(()));
+        wheel.insert(&ENTRY);
+        assert_eq!(wheel.skip(), Some(20));
+        assert_eq!(wheel.wake(|_| {}), 1);
+    }
+
+    #[test]
+    fn atomic_priority_test() {
+        let mut wheel = Wheel::<atomic::ArcEntry>::default();
+        let entries = [
+            atomic::Entry::with_priority(300, 2),
+            atomic::Entry::with_priority(300, 1),
+            atomic::Entry::with_priority(290, 5),
+            atomic::Entry::with_priority(300, 2),
+            atomic::Entry::with_priority(300, 0),
+            atomic::Entry::with_priority(70_000, 0),
+        ];
+        for entry in entries.iter() {
+            wheel.insert(entry.clone());
+        }
+
+        wheel.catch_up(1000);
+
+        let late = atomic::Entry::with_priority(0, 3);
+        wheel.insert_deadline(late.clone(), 300);
+
+        let mut woken = vec![];
+        wheel.wake(|entry| woken.push(Arc::as_ptr(&entry)));
+
+        let expected: Vec<_> = [2, 4, 1, 0, 3]
+            .iter()
+            .map(|index| Arc::as_ptr(&entries[*index]))
+            .chain(Some(Arc::as_ptr(&late)))
+            .collect();
+        assert_eq!(woken, expected);
+        assert_eq!(wheel.len(), 1);
+    }
+
+    #[test]
+    fn fifo_test() {
+        const DEADLINES: [u64; 6] = [300, 1000, 65_536, 70_000, 1 << 24, (1 << 24) + 5];
+
+        let ops = gen::<Vec<(u8, u16)>>();
+
+        TestTarget::new(RngEngine::default().with_iterations(ITERATIONS))
+            .with_generator(ops)
+            .for_each(|ops| {
+                let mut wheel = Wheel::default();
+                wheel.set_cascade_budget(ops.len() % 3 * 4);
+                let mut entries = vec![];
+
+                for (index, (deadline, advance)) in ops.iter().copied().enumerate() {
+                    let deadline = DEADLINES[deadline as usize % DEADLINES.len()];
+                    let now = wheel.ticks();
+                    let entry = atomic::Entry::new(deadline.saturating_sub(now));
+                    entries.push(entry.clone());
+
+                    if deadline > now {
+                        wheel.insert(entry);
+                    } else {
+                        wheel.insert_deadline(entry, deadline);
+                    }
+
+                    let elapsed = advance as u64 * 97;
+                    if index % 2 == 0 {
+                        wheel.advance_to(now + elapsed);
+                    } else {
+                        wheel.catch_up(elapsed);
+                    }
+                }
+
+                let mut woken = vec![];
+                loop {
+                    wheel.wake(|entry| {
+                        let index = entries.iter().position(|e| Arc::ptr_eq(e, &entry)).unwrap();
+                        woken.push((entry.deadline(), index));
+                    });
+                    if wheel.skip().is_none() {
+                        break;
+                    }
+                }
+
+                assert_eq!(woken.len(), entries.len());
+                for deadline in DEADLINES.iter() {
+                    let order: Vec<_> = woken
+                        .iter()
+                        .filter(|(d, _)| d == deadline)
+                        .map(|(_, index)| *index)
+                        .collect();
+                    assert!(order.windows(2).all(|w| w[0] < w[1]), "{:?}", woken);
+                }
+            });
+    }
+
+    #[test]
+    fn wake_up_to_test() {
+        let mut wheel = Wheel::default();
+        let entries: Vec<_> = core::iter::repeat_with(|| atomic::Entry::new(5))
+            .take(10)
+            .collect();
+        for entry in entries.iter() {
+            wheel.insert(entry.clone());
+        }
+        assert!(!wheel.has_pending());
+        assert_eq!(wheel.wake_up_to(3, atomic::wake), 0);
+
+        assert_eq!(wheel.skip(), Some(5));
+        assert!(wheel.has_pending());
+
+        let mut woken = vec![];
+        let mut counts = vec![];
+        while wheel.has_pending() {
+            counts.push(wheel.wake_up_to(3, |entry| woken.push(entry)));
+        }
+
+        assert_eq!(counts, [3, 3, 3, 1]);
+        assert!(woken.iter().zip(&entries).all(|(a, b)| Arc::ptr_eq(a, b)));
+        assert_eq!(wheel.stats().woken, 10);
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.len(), 0);
+    }
+
+    #[test]
+    fn expired_test() {
+        let mut wheel = Wheel::default();
+        let entries: Vec<_> = core::iter::repeat_with(|| atomic::Entry::new(5))
+            .take(4)
+            .collect();
+        for entry in entries.iter() {
+            wheel.insert(entry.clone());
+        }
+        assert_eq!(wheel.expired().count(), 0);
+
+        wheel.advance_to(5);
+
+        // stop part way through; the rest stays pending
+        for (index, entry) in wheel.expired().enumerate() {
+            assert!(Arc::ptr_eq(&entry, &entries[index]));
+            if index == 1 {
+                break;
+            }
+        }
+        assert_eq!(wheel.len(), 2);
+        assert_eq!(wheel.stats().woken, 2);
+        assert!(wheel.has_pending());
+
+        let rest: Vec<_> = wheel.expired().collect();
+        assert!(rest
+            .iter()
+            .zip(&entries[2..])
+            .all(|(a, b)| Arc::ptr_eq(a, b)));
+        assert!(!wheel.has_pending());
+        assert_eq!(wheel.len(), 0);
+    }
+
+    #[test]
+    fn poll_test() {
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 24));
+        let steps = gen::<Vec<u64>>().with().values(0..(1u64 << 20));
+
+        check!()
+            .with_generator((delays, steps))
+            .for_each(|(delays, steps)| {
+                let mut wheel = Wheel::default();
+                for delay in delays.iter().copied() {
+                    wheel.insert(atomic::Entry::new(delay));
+                }
+
+                let mut remaining = delays.clone();
+                remaining.sort_unstable();
+                let mut remaining = &remaining[..];
+                let mut now = 0;
+
+                for step in steps.iter().copied().chain(Some(u64::MAX >> 1)) {
+                    now += step;
+                    let poll = wheel.poll(now, |entry| {
+                        assert!(entry.deadline() <= now);
+                    });
+
+                    let due = remaining.iter().take_while(|d| **d <= now).count();
+                    remaining = &remaining[due..];
+
+                    assert_eq!(poll.woken, due);
+                    assert_eq!(poll.next_deadline, remaining.first().copied());
+                    assert_eq!(wheel.ticks(), now);
+                }
+
+                assert!(wheel.is_empty());
+                assert_eq!(
+                    wheel.poll(0, atomic::wake),
+                    Poll {
+                        woken: 0,
+                        next_deadline: None,
+                    }
+                );
+            });
+    }
+
+    #[test]
+    fn pre_cascade_fifo_test() {
+        let mut wheel = Wheel::default();
+        wheel.set_cascade_budget(1);
+
+        let entries: Vec<_> = core::iter::repeat_with(|| atomic::Entry::new(600))
+            .take(3)
+            .collect();
+        for entry in entries.iter() {
+            wheel.insert(entry.clone());
+        }
+
+        // moves the first entry into the lowest stack ahead of time
+        wheel.advance_to(356);
+        assert_eq!(wheel.stack_stats().next().unwrap().entries, 1);
+
+        // catching up within the rotation leaves it in place
+        wheel.catch_up(50);
+        assert_eq!(wheel.stack_stats().next().unwrap().entries, 1);
+        assert_eq!(wheel.next_alarm(1000), 600 - 406);
+
+        let mut woken = vec![];
+        while wheel.skip().is_some() {
+            wheel.wake(|entry| woken.push(entry));
+        }
+        assert!(woken.iter().zip(&entries).all(|(a, b)| Arc::ptr_eq(a, b)));
+        assert_eq!(woken.len(), 3);
+    }
+
+    #[test]
+    fn max_cascade_test() {
+        const COUNT: u64 = 10_000;
+
+        let run = |first: u64, budget: usize| {
+            let mut wheel = Wheel::default();
+            wheel.set_cascade_budget(budget);
+
+            // a large slot in the second stack, due in the third rotation
+            for index in 0..COUNT {
+                wheel.insert(atomic::Entry::new(first + index * 255 / COUNT));
+            }
+
+            let mut woken = 0;
+            for now in 1..1024 {
+                wheel.advance_to(now);
+                wheel.wake_with_info(|_, info| {
+                    assert_eq!(info.lateness, 0);
+                    woken += 1;
+                });
+            }
+
+            assert_eq!(woken, COUNT);
+            wheel.stats().max_cascade
+        };
+
+        // the entries at the front of the slot are due at the start of the
+        // rotation, in slot 0 of the lowest stack, or one tick later
+        for first in [512, 513].iter().copied() {
+            assert_eq!(run(first, 0), COUNT as usize);
+            let max_cascade = run(first, 64);
+            assert!(max_cascade <= 128, "{}", max_cascade);
+        }
+    }
+
+    #[test]
+    fn catch_up_budget_test() {
+        let mut wheel = Wheel::default();
+        wheel.set_cascade_budget(16);
+        wheel.insert(atomic::Entry::new(456));
+        for now in 1..=210 {
+            wheel.advance_to(now);
+        }
+
+        // the entry was cascaded ahead of time behind the lowest cursor and is
+        // still due after catching up, in the same slot
+        wheel.catch_up(100);
+        assert_eq!(wheel.ticks(), 310);
+        assert_eq!(wheel.wake(atomic::wake), 0);
+        assert_eq!(wheel.skip(), Some(146));
+        assert_eq!(wheel.wake(atomic::wake), 1);
+    }
+
+    #[test]
+    fn pre_cascade_skip_test() {
+        // entries cascaded ahead of time into slot 0 of the lowest stack must
+        // not let the stacks above skip past the slot they came from
+        let mut wheel = Wheel::default();
+        wheel.set_cascade_budget(1);
+        wheel.advance_to(1_138_915);
+        for delay in [29, 1_511_881, 2].iter() {
+            wheel.insert(atomic::Entry::new(*delay));
+        }
+
+        wheel.advance_to(1_198_513);
+        let mut woken = vec![];
+        wheel.wake_with_info(|_, info| woken.push(info.deadline));
+        assert_eq!(woken, [1_138_917, 1_138_944]);
+        assert_eq!(wheel.len(), 1);
+
+        assert_eq!(wheel.skip(), Some(2_650_796 - 1_198_513));
+        assert_eq!(wheel.wake(atomic::wake), 1);
+    }
+
+    #[test]
+    fn skip_cascade_budget_test() {
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 24));
+
+        TestTarget::new(RngEngine::default().with_iterations(ITERATIONS))
+            .with_generator((delays, 1usize..4))
+            .for_each(|(delays, budget)| {
+                let mut wheel = Wheel::default();
+                wheel.set_cascade_budget(*budget);
+                for delay in delays.iter() {
+                    wheel.insert(atomic::Entry::new(*delay));
+                }
+
+                let mut woken = 0;
+                loop {
+                    woken += wheel.wake_with_info(|_, info| {
+                        assert_eq!(info.now, info.deadline);
+                    });
+                    if wheel.skip().is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(woken, delays.len());
+            });
+    }
+
+    #[test]
+    fn split_append_test() {
+        let delays = gen::<Vec<u64>>().with().values(0..(1u64 << 30));
+        let advance = 0..(1u64 << 20);
+
+        check!()
+            .with_generator((delays, advance.clone(), advance))
+            .for_each(|(delays, first, second)| {
+                let mut wheel = Wheel::default();
+                let entries: Vec<_> = delays
+                    .iter()
+                    .map(|delay| atomic::Entry::new(*delay))
+                    .collect();
+                for entry in entries.iter() {
+                    wheel.insert(entry.clone());
+                }
+                wheel.advance_to(*first);
+
+                let remaining = |wheel: &Wheel<atomic::ArcEntry>| {
+                    let now = wheel.ticks();
+                    let mut remaining: Vec<_> = wheel
+                        .iter()
+                        .map(|(entry, deadline)| {
+                            let index = entries.iter().position(|e| Arc::ptr_eq(e, &entry));
+                            (index.unwrap(), deadline.saturating_sub(now))
+                        })
+                        .collect();
+                    remaining.sort_unstable();
+                    remaining
+                };
+                let expected = remaining(&wheel);
+
+                let mut odd = wheel.split_off(|entry| entry.delay() % 2 == 1);
+                assert_eq!(odd.ticks(), wheel.ticks());
+                assert_eq!(odd.len() + wheel.len(), entries.len());
+                assert!(wheel.iter().all(|(entry, _)| entry.delay() % 2 == 0));
+                assert!(odd.iter().all(|(entry, _)| entry.delay() % 2 == 1));
+
+                // move the split entries to a wheel at a different tick
+                let mut other = Wheel::default();
+                other.advance_to(*second);
+                other.append(&mut odd);
+                assert!(odd.is_empty());
+                assert_eq!(odd.stats().len, 0);
+                assert_eq!(other.len(), entries.len() - wheel.len());
+
+                other.append(&mut wheel);
+                assert_eq!(remaining(&other), expected);
+                assert_eq!(other.len(), entries.len());
+
+                let mut woken = 0;
+                loop {
+                    woken += other.wake(atomic::wake);
+                    if other.skip().is_none() {
+                        break;
+                    }
+                }
+                assert_eq!(woken, entries.len());
+            });
+    }
+
+    #[test]
+    fn atomic_state_test() {
+        use atomic::State;
+
+        let mut wheel = Wheel::default();
+        let entry = atomic::Entry::new(10);
+        assert_eq!(entry.state(), State::Idle);
+        assert!(!entry.take_expired());
+
+        assert!(entry.should_register());
+        assert!(!entry.should_register());
+        wheel.insert(entry.clone());
+        assert_eq!(entry.state(), State::Scheduled);
+
+        wheel.advance_to(10);
+        assert_eq!(entry.state(), State::Pending);
+        wheel.wake(atomic::wake);
+        assert_eq!(entry.state(), State::Fired);
+
+        // cancelling after firing doesn't hide the expiration
+        entry.cancel();
+        assert_eq!(entry.state(), State::Fired);
+        assert!(entry.take_expired());
+        assert_eq!(entry.state(), State::Idle);
+
+        wheel.insert(entry.clone());
+        assert!(wheel.cancel(&entry).is_some());
+        assert_eq!(entry.state(), State::Cancelled);
+
+        // an entry cancelled while it is still in the wheel is never fired
+        wheel.insert(entry.clone());
+        entry.cancel();
+        wheel.advance_to(20);
+        assert_eq!(entry.state(), State::Cancelled);
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert_eq!(entry.state(), State::Cancelled);
+        assert!(!entry.take_expired());
+
+        assert!(entry.should_register());
+        assert_eq!(entry.state(), State::Scheduled);
+    }
+
+    #[test]
+    fn atomic_cancel_before_insert_test() {
+        use atomic::State;
+
+        let mut wheel = Wheel::default();
+        let entry = atomic::Entry::new(10);
+
+        // the entry is cancelled between being registered and inserted
+        assert!(entry.should_register());
+        assert!(entry.cancel());
+        wheel.insert(entry.clone());
+        assert_eq!(entry.state(), State::Cancelled);
+
+        wheel.advance_to(10);
+        assert_eq!(entry.state(), State::Cancelled);
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert_eq!(entry.state(), State::Cancelled);
+        assert!(!entry.take_expired());
+
+        // a fired entry can still be inserted again directly
+        assert!(entry.should_register());
+        wheel.insert(entry.clone());
+        wheel.advance_to(20);
+        wheel.wake(atomic::wake);
+        assert_eq!(entry.state(), State::Fired);
+        wheel.insert(entry.clone());
+        assert_eq!(entry.state(), State::Scheduled);
+    }
+
+    #[test]
+    fn atomic_reschedule_test() {
+        use alloc::task::Wake;
+        use atomic::State;
+        use core::{
+            sync::atomic::{AtomicUsize, Ordering},
+            task::Waker,
+        };
+
+        #[derive(Default)]
+        struct CountingWaker(AtomicUsize);
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        type Restart = fn(&mut Wheel<atomic::ArcEntry>, &atomic::ArcEntry);
+        let restarts: [Restart; 3] = [
+            |wheel, entry| assert!(wheel.reschedule(entry)),
+            |wheel, entry| wheel.insert(entry.clone()),
+            |wheel, entry| {
+                let deadline = wheel.ticks() + 10;
+                wheel.insert_deadline(entry.clone(), deadline)
+            },
+        ];
+
+        for restart in restarts.iter() {
+            let mut wheel = Wheel::default();
+            let entry = atomic::Entry::new(10);
+            let counter = Arc::new(CountingWaker::default());
+            entry.register(&Waker::from(counter.clone()));
+
+            assert!(entry.should_register());
+            wheel.insert(entry.clone());
+            wheel.advance_to(5);
+
+            // keeping the entry alive restarts its timer without cancelling it
+            restart(&mut wheel, &entry);
+            assert_eq!(entry.state(), State::Scheduled);
+
+            wheel.advance_to(15);
+            assert_eq!(wheel.wake(atomic::wake), 1);
+            assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+            assert!(entry.take_expired());
+        }
+    }
+
     #[test]
     fn empty_test() {
         let mut wheel = Wheel::default();
@@ -335,6 +2720,89 @@ mod tests {
         test_helper(&[&[1, 489][..], &[24, 279][..]]);
     }
 
+    #[test]
+    fn advance_to_test() {
+        check!()
+            .with_type::<(Vec<u32>, u32)>()
+            .for_each(|(delays, target)| {
+                let mut wheel = Wheel::default();
+                for delay in delays.iter() {
+                    wheel.insert(atomic::Entry::new(*delay as u64));
+                }
+
+                let target = *target as u64;
+                assert_eq!(wheel.advance_to(target), target);
+                assert_eq!(wheel.ticks(), target);
+
+                let expired = delays.iter().filter(|d| **d as u64 <= target).count();
+                assert_eq!(wheel.wake(atomic::wake), expired);
+                assert_eq!(wheel.len(), delays.len() - expired);
+
+                // the remaining entries still expire on their deadlines
+                let mut remaining: Vec<_> = delays
+                    .iter()
+                    .map(|d| *d as u64)
+                    .filter(|d| *d > target)
+                    .collect();
+                remaining.sort_unstable();
+                remaining.dedup();
+                for deadline in remaining {
+                    let delta = deadline - wheel.ticks();
+                    assert_eq!(wheel.skip(), Some(delta));
+                    assert_eq!(wheel.ticks(), deadline);
+                    wheel.wake(atomic::wake);
+                }
+                assert!(wheel.is_empty());
+            });
+    }
+
+    #[test]
+    fn reschedule_test() {
+        let mut wheel = Wheel::default();
+        let entry = atomic::Entry::new(10);
+        wheel.insert(entry.clone());
+
+        assert_eq!(wheel.skip(), Some(10));
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert!(!wheel.reschedule(&entry));
+
+        wheel.insert(entry.clone());
+        wheel.advance_to(15);
+        assert!(wheel.reschedule(&entry));
+        assert_eq!(wheel.len(), 1);
+
+        // the delay restarts from the tick it was rescheduled at
+        assert_eq!(wheel.skip(), Some(10));
+        assert_eq!(wheel.ticks(), 25);
+        assert_eq!(wheel.wake(atomic::wake), 1);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn placement_test() {
+        // each entry shares all but its lowest byte with the current tick,
+        // so it has to be placed by the highest byte that differs
+        for delay in [0x101u64, 0x1_0001, 0x100_0001, 1 << 40 | 1].iter() {
+            let mut wheel = Wheel::default();
+            wheel.insert(atomic::Entry::new(*delay));
+
+            let index = 7 - delay.leading_zeros() as usize / 8;
+            let stats = wheel.stack_stats().nth(index).unwrap();
+            assert_eq!(stats.entries, 1, "delay: {:#x}", delay);
+
+            assert_eq!(wheel.skip(), Some(*delay), "delay: {:#x}", delay);
+        }
+    }
+
+    #[test]
+    fn wrapping_test() {
+        // the elapsed time across the end of the range includes the wrap itself
+        assert_eq!(2u64.elapsed_since(u64::MAX), 3);
+        assert_eq!(2u32.elapsed_since(u32::MAX), 3);
+
+        test_helper(&[&[u64::MAX - 3][..], &[10, 1 << 40][..]]);
+    }
+
     #[test]
     fn overflow_test() {
         test_helper(&[